@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use evalexpr::{Context, EvalexprResult, HashMapContext, Value};
+
+use crate::functions::create_function_context;
+
+/// Context evaluating the expressions for a line.
+/// Variables are kept apart from the functions, so they can change their type,
+/// like a missing value after a number, without creating the functions and their regex cache again
+pub struct LineContext {
+	functions: HashMapContext,
+	variables: HashMap<String, Value>,
+}
+
+impl LineContext {
+	pub fn new() -> LineContext {
+		LineContext {
+			functions: create_function_context(),
+			variables: HashMap::new(),
+		}
+	}
+
+	/// set the variable `name` to `value`, which may have another type than before
+	pub fn set_value(&mut self, name: &str, value: Value) {
+		match self.variables.get_mut(name) {
+			Some(variable) => *variable = value,
+			None => {
+				self.variables.insert(name.to_owned(), value);
+			}
+		}
+	}
+}
+
+impl Context for LineContext {
+	fn get_value(&self, identifier: &str) -> Option<&Value> {
+		self.variables.get(identifier)
+	}
+
+	fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+		self.functions.call_function(identifier, argument)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn variables_change_type() {
+		let mut ctx = LineContext::new();
+		let node = evalexpr::build_operator_tree("coalesce(c1, 0) > 5").unwrap();
+
+		ctx.set_value("c1", Value::Int(10));
+		assert!(node.eval_boolean_with_context(&ctx).unwrap());
+
+		ctx.set_value("c1", Value::Empty);
+		assert!(!node.eval_boolean_with_context(&ctx).unwrap());
+
+		ctx.set_value("c1", Value::Float(7.5));
+		assert!(node.eval_boolean_with_context(&ctx).unwrap());
+	}
+}
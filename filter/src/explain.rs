@@ -0,0 +1,147 @@
+use evalexpr::{Context, Node};
+use io_utils::LineBuffer;
+use std::fmt::Write;
+use std::io::BufRead;
+
+use crate::context::LineContext;
+use crate::{
+	compile_expressions, mutate_context_for_line, read_data_lines, read_header, ColumnType,
+	CompiledExpressions, FilterOptions, NaComparisons, FIELD_COUNT_VARIABLE, LINE_NUMBER_VARIABLE,
+	LINE_VARIABLE,
+};
+
+/// describe how `expressions` are parsed, and evaluate them for the first `lines` data lines
+/// of `input_reader`, without writing any output
+pub fn explain_expressions(
+	input_reader: &mut impl BufRead,
+	expressions: &[String],
+	options: &FilterOptions,
+	lines: usize,
+) -> Result<String, anyhow::Error> {
+	let mut buffer = LineBuffer::default();
+	let header_names = read_header(&mut buffer, input_reader, options)?;
+	buffer.clear();
+
+	// data lines used for type inference and evaluation
+	read_data_lines(
+		&mut buffer,
+		input_reader,
+		options,
+		options.infer_lines.max(lines),
+	)?;
+
+	let CompiledExpressions {
+		column_types,
+		declared_types,
+		expressions: compiled,
+		columns,
+		line_variables,
+	} = compile_expressions(expressions, &buffer, &header_names, options)?;
+
+	let mut explanation = String::new();
+
+	for (expression, compiled) in expressions.iter().zip(&compiled) {
+		writeln!(explanation, "Expression: {expression}")?;
+		writeln!(explanation, "Operator tree:")?;
+		write_tree(&mut explanation, &compiled.node, 1)?;
+		writeln!(explanation, "Columns:")?;
+
+		for column in &compiled.columns {
+			// columns without declared type have an inferred type
+			let source = if column.index < declared_types {
+				"declared"
+			} else {
+				"inferred"
+			};
+
+			writeln!(
+				explanation,
+				"  {} (column {}): {} ({source})",
+				column.name,
+				column.index + 1,
+				column_types.get(column.index).unwrap_or(&ColumnType::None)
+			)?;
+		}
+
+		writeln!(explanation)?;
+	}
+
+	let used_line_variables = [
+		(LINE_NUMBER_VARIABLE, line_variables.line_number),
+		(FIELD_COUNT_VARIABLE, line_variables.field_count),
+		(LINE_VARIABLE, line_variables.line),
+	];
+
+	let mut context = LineContext::new();
+
+	let data = buffer.iter().filter(|(_, l)| options.is_data_line(l));
+
+	for (line_number, line) in data.take(lines) {
+		// line numbers are 0 based internally, but 1 based like NR in the expression
+		writeln!(explanation, "Line {}:", line_number + 1)?;
+
+		let result = mutate_context_for_line(
+			line,
+			line_number,
+			&column_types,
+			&columns,
+			line_variables,
+			options,
+			&mut context,
+		);
+
+		let na_columns = match result {
+			Ok(na_columns) => na_columns,
+			Err(e) => {
+				writeln!(explanation, "  invalid line: {e}")?;
+				continue;
+			}
+		};
+
+		let names = used_line_variables
+			.iter()
+			.filter(|(_, used)| *used)
+			.map(|(name, _)| *name)
+			.chain(columns.iter().map(|c| c.name.as_str()));
+
+		for name in names {
+			let value = match context.get_value(name) {
+				Some(v) => v.to_string(),
+				None => String::new(),
+			};
+
+			writeln!(explanation, "  {name} = {value}")?;
+		}
+
+		for (expression, compiled) in expressions.iter().zip(&compiled) {
+			match (
+				compiled.evaluate(&context, &na_columns),
+				options.na_comparisons,
+			) {
+				(Ok(Some(passed)), _) => writeln!(explanation, "  {expression} -> {passed}")?,
+				(Ok(None), NaComparisons::False) => writeln!(
+					explanation,
+					"  {expression} -> false, comparison with a missing value"
+				)?,
+				(Ok(None), NaComparisons::Invalid) => writeln!(
+					explanation,
+					"  {expression} -> invalid, comparison with a missing value"
+				)?,
+				(Err(e), _) => writeln!(explanation, "  {expression} -> {e}")?,
+			}
+		}
+	}
+
+	Ok(explanation)
+}
+
+/// write the operators of `node` and its children, indented by `depth`
+fn write_tree(out: &mut String, node: &Node, depth: usize) -> std::fmt::Result {
+	writeln!(out, "{}{:?}", "  ".repeat(depth), node.operator())?;
+
+	for child in node.children() {
+		write_tree(out, child, depth + 1)?;
+	}
+
+	Ok(())
+}
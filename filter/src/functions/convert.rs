@@ -0,0 +1,119 @@
+use evalexpr::{
+	ContextWithMutableFunctions, EvalexprError, Function, HashMapContext, Value, ValueType,
+};
+
+/// error for a value that can't be converted to `expected`
+fn conversion_error(expected: ValueType, actual: &Value) -> EvalexprError {
+	EvalexprError::TypeError {
+		expected: vec![expected],
+		actual: actual.to_owned(),
+	}
+}
+
+/// adds the type conversion functions `int`, `float` and `str` to `context`
+pub fn add_conversion_functions(context: &mut HashMapContext) {
+	let functions = [
+		(
+			"int",
+			Function::new(|argument| match argument {
+				Value::Int(i) => Ok(Value::Int(*i)),
+				// like python, floats are truncated
+				Value::Float(f) => Ok(Value::Int(f.trunc() as i64)),
+				Value::Boolean(b) => Ok(Value::Int(*b as i64)),
+				Value::String(s) => match s.trim().parse::<i64>() {
+					Ok(i) => Ok(Value::Int(i)),
+					Err(_) => Err(conversion_error(ValueType::Int, argument)),
+				},
+				v => Err(conversion_error(ValueType::Int, v)),
+			}),
+		),
+		(
+			"float",
+			Function::new(|argument| match argument {
+				Value::Int(i) => Ok(Value::Float(*i as f64)),
+				Value::Float(f) => Ok(Value::Float(*f)),
+				Value::String(s) => match s.trim().parse::<f64>() {
+					Ok(f) => Ok(Value::Float(f)),
+					Err(_) => Err(conversion_error(ValueType::Float, argument)),
+				},
+				v => Err(conversion_error(ValueType::Float, v)),
+			}),
+		),
+		(
+			"str",
+			Function::new(|argument| match argument {
+				Value::String(s) => Ok(Value::String(s.to_owned())),
+				Value::Int(i) => Ok(Value::String(i.to_string())),
+				Value::Float(f) if f.fract() == 0.0 && f.is_finite() => {
+					Ok(Value::String(format!("{f:.1}")))
+				}
+				Value::Float(f) => Ok(Value::String(f.to_string())),
+				Value::Boolean(b) => Ok(Value::String(b.to_string())),
+				v => Err(conversion_error(ValueType::String, v)),
+			}),
+		),
+	];
+
+	for (identifier, function) in functions {
+		context
+			.set_function(identifier.to_owned(), function)
+			.unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::functions::create_function_context;
+	use evalexpr::{eval_with_context, Value};
+
+	#[test]
+	fn int_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_with_context(r#"int("42")"#, &ctx).unwrap(),
+			Value::Int(42)
+		);
+		assert_eq!(
+			eval_with_context("int(-2.7)", &ctx).unwrap(),
+			Value::Int(-2)
+		);
+		assert_eq!(eval_with_context("int(true)", &ctx).unwrap(), Value::Int(1));
+
+		assert!(eval_with_context(r#"int("1.5")"#, &ctx).is_err());
+	}
+
+	#[test]
+	fn float_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_with_context(r#"float("1.5")"#, &ctx).unwrap(),
+			Value::Float(1.5)
+		);
+		assert_eq!(
+			eval_with_context("float(2)", &ctx).unwrap(),
+			Value::Float(2.0)
+		);
+
+		assert!(eval_with_context(r#"float("foo")"#, &ctx).is_err());
+	}
+
+	#[test]
+	fn str_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_with_context("str(42)", &ctx).unwrap(),
+			Value::String("42".to_owned())
+		);
+		assert_eq!(
+			eval_with_context("str(2.0)", &ctx).unwrap(),
+			Value::String("2.0".to_owned())
+		);
+		assert_eq!(
+			eval_with_context("str(0.25)", &ctx).unwrap(),
+			Value::String("0.25".to_owned())
+		);
+	}
+}
@@ -0,0 +1,177 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime};
+use evalexpr::{
+	ContextWithMutableFunctions, EvalexprError, EvalexprResult, Function, HashMapContext, Value,
+};
+
+/// Format of dates, unless another format is given
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Formats of datetimes without time zone, tried in order unless another format is given.
+/// Datetimes with time zone are parsed as RFC 3339
+const DATETIME_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// parse a date to seconds since 1970-01-01, using `format` or `%Y-%m-%d`
+pub fn parse_date(value: &str, format: Option<&str>) -> Option<i64> {
+	let date = NaiveDate::parse_from_str(value, format.unwrap_or(DATE_FORMAT)).ok()?;
+
+	Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp())
+}
+
+/// parse a datetime to seconds since 1970-01-01 UTC.
+/// Without `format`, ISO 8601 datetimes are accepted, with or without time zone
+pub fn parse_datetime(value: &str, format: Option<&str>) -> Option<i64> {
+	if let Some(format) = format {
+		return match NaiveDateTime::parse_from_str(value, format) {
+			Ok(datetime) => Some(datetime.and_utc().timestamp()),
+			// formats with time zone
+			Err(_) => DateTime::parse_from_str(value, format)
+				.ok()
+				.map(|d| d.timestamp()),
+		};
+	}
+
+	if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+		return Some(datetime.timestamp());
+	}
+
+	DATETIME_FORMATS
+		.iter()
+		.find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+		.map(|datetime| datetime.and_utc().timestamp())
+}
+
+/// format the date at `seconds` since 1970-01-01 with `format`
+pub fn format_date(seconds: i64, format: &str) -> String {
+	match DateTime::from_timestamp(seconds, 0) {
+		Some(datetime) => datetime.format(format).to_string(),
+		None => String::new(),
+	}
+}
+
+/// whether `format` is valid, and the dates it formats can be parsed with `parse`.
+/// Formats like `%Y-%m` can not be parsed, as a date needs a day
+pub fn is_parsable_format(format: &str, parse: fn(&str, Option<&str>) -> Option<i64>) -> bool {
+	!StrftimeItems::new(format).any(|item| item == Item::Error)
+		&& parse(&format_date(0, format), Some(format)).is_some()
+}
+
+/// function parsing a string with `parse`, and an optional format as second argument
+fn parsing_function(parse: fn(&str, Option<&str>) -> Option<i64>, name: &'static str) -> Function {
+	Function::new(move |argument| {
+		let (value, format) = match argument {
+			Value::Tuple(_) => {
+				let arguments = argument.as_fixed_len_tuple(2)?;
+				(arguments[0].as_string()?, Some(arguments[1].as_string()?))
+			}
+			v => (v.as_string()?, None),
+		};
+
+		match parse(&value, format.as_deref()) {
+			Some(seconds) => Ok(Value::Int(seconds)),
+			None => Err(EvalexprError::CustomMessage(format!(
+				"'{value}' is not a valid {name}"
+			))),
+		}
+	})
+}
+
+/// get the UTC datetime of a date or datetime value
+fn as_datetime(value: &Value) -> EvalexprResult<DateTime<chrono::Utc>> {
+	let seconds = value.as_int()?;
+
+	match DateTime::from_timestamp(seconds, 0) {
+		Some(datetime) => Ok(datetime),
+		None => Err(EvalexprError::CustomMessage(format!(
+			"{seconds} is out of the range of dates"
+		))),
+	}
+}
+
+/// function getting a part of a date or datetime with `f`
+fn date_part_function(f: fn(&DateTime<chrono::Utc>) -> i64) -> Function {
+	Function::new(move |argument| Ok(Value::Int(f(&as_datetime(argument)?))))
+}
+
+/// adds date functions to `context`.
+/// Dates and datetimes are ints, counting the seconds since 1970-01-01 UTC,
+/// so they can be compared and subtracted
+pub fn add_date_functions(context: &mut HashMapContext) {
+	let functions = [
+		("date", parsing_function(parse_date, "date")),
+		("datetime", parsing_function(parse_datetime, "datetime")),
+		("year", date_part_function(|d| d.year() as i64)),
+		("month", date_part_function(|d| d.month() as i64)),
+		("day", date_part_function(|d| d.day() as i64)),
+		(
+			"days_between",
+			Function::new(|argument| {
+				let arguments = argument.as_fixed_len_tuple(2)?;
+				let seconds = arguments[1].as_int()? - arguments[0].as_int()?;
+
+				// whole days, rounded down like python's timedelta
+				Ok(Value::Int(seconds.div_euclid(86400)))
+			}),
+		),
+	];
+
+	for (identifier, function) in functions {
+		context
+			.set_function(identifier.to_owned(), function)
+			.unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::functions::create_function_context;
+	use evalexpr::{eval_boolean_with_context, eval_with_context};
+
+	#[test]
+	fn parses_dates() {
+		assert_eq!(parse_date("1970-01-02", None), Some(86400));
+		assert_eq!(parse_date("02.01.1970", Some("%d.%m.%Y")), Some(86400));
+		assert_eq!(parse_date("2024-02-30", None), None);
+
+		assert_eq!(parse_datetime("1970-01-01T00:01:00", None), Some(60));
+		assert_eq!(parse_datetime("1970-01-01 00:01:00.5", None), Some(60));
+		assert_eq!(parse_datetime("1970-01-01T01:00:00+01:00", None), Some(0));
+		assert_eq!(
+			parse_datetime("01/01/1970 00:00", Some("%m/%d/%Y %H:%M")),
+			Some(0)
+		);
+		assert_eq!(parse_datetime("1970-01-01", None), None);
+
+		assert_eq!(format_date(86400, DATE_FORMAT), "1970-01-02");
+	}
+
+	#[test]
+	fn date_functions() {
+		let ctx = create_function_context();
+
+		assert!(eval_boolean_with_context(
+			r#"datetime("2024-01-01T08:00:00") >= date("2024-01-01")"#,
+			&ctx
+		)
+		.unwrap());
+		assert_eq!(
+			eval_with_context(r#"year(date("2024-03-15"))"#, &ctx).unwrap(),
+			Value::Int(2024)
+		);
+		assert_eq!(
+			eval_with_context(r#"month(date("15/03/2024", "%d/%m/%Y"))"#, &ctx).unwrap(),
+			Value::Int(3)
+		);
+		assert_eq!(
+			eval_with_context(
+				r#"days_between(date("2024-02-01"), datetime("2024-03-01 12:00:00"))"#,
+				&ctx
+			)
+			.unwrap(),
+			Value::Int(29)
+		);
+
+		assert!(eval_with_context(r#"date("2024-13-01")"#, &ctx).is_err());
+	}
+}
@@ -0,0 +1,356 @@
+use evalexpr::{
+	ContextWithMutableFunctions, EvalexprError, EvalexprResult, Function, HashMapContext, Value,
+};
+
+/// An interval on a chromosome, 0-based and half-open like in BED files
+#[derive(Debug, PartialEq)]
+struct Interval {
+	chrom: String,
+	start: i64,
+	end: i64,
+}
+
+impl Interval {
+	fn overlaps(&self, other: &Interval) -> bool {
+		self.chrom == other.chrom && self.start < other.end && other.start < self.end
+	}
+
+	fn within(&self, other: &Interval) -> bool {
+		self.chrom == other.chrom && other.start <= self.start && self.end <= other.end
+	}
+
+	/// amount of bases between both intervals, 0 if they overlap or touch.
+	/// Intervals on different chromosomes have no distance
+	fn distance(&self, other: &Interval) -> Option<i64> {
+		if self.chrom != other.chrom {
+			None
+		} else if self.end <= other.start {
+			Some(other.start - self.end)
+		} else if other.end <= self.start {
+			Some(self.start - other.end)
+		} else {
+			Some(0)
+		}
+	}
+}
+
+/// Coordinate system of interval columns
+#[derive(Clone, Copy)]
+enum Coordinates {
+	/// 0-based, half-open, as in BED files
+	Bed,
+	/// 1-based, closed, as in GFF, SAM and VCF files
+	OneBased,
+}
+
+impl Coordinates {
+	fn parse(value: &Value) -> EvalexprResult<Coordinates> {
+		match value.as_string()?.as_str() {
+			"bed" | "0-based" => Ok(Coordinates::Bed),
+			"gff" | "gtf" | "sam" | "vcf" | "1-based" => Ok(Coordinates::OneBased),
+			c => Err(EvalexprError::CustomMessage(format!(
+				"Unknown coordinates '{c}'. Expected bed or 0-based, gff, gtf, sam, vcf or 1-based"
+			))),
+		}
+	}
+
+	/// convert `start` and `end` in these coordinates to 0-based, half-open
+	fn to_bed(self, start: i64, end: i64) -> (i64, i64) {
+		match self {
+			Coordinates::Bed => (start, end),
+			Coordinates::OneBased => (start - 1, end),
+		}
+	}
+}
+
+/// whether `name` is a whole contig containing `:`, like the HLA alleles `HLA-A*01:01:01:01` of GRCh38.
+/// Alleles have at most four fields, so `HLA-A*01:01:01:01:100` is a position on an allele
+fn is_known_contig(name: &str) -> bool {
+	match name.strip_prefix("HLA-").and_then(|n| n.split_once('*')) {
+		Some((_, allele)) => allele.split(':').count() <= 4 && !allele.contains('-'),
+		None => false,
+	}
+}
+
+/// whether `range` is a position like `100` or `1,000`, or a range like `100-200`
+fn is_range(range: &str) -> bool {
+	let is_position = |p: &str| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit() || c == ',');
+
+	match range.split_once('-') {
+		Some((start, end)) => is_position(start) && is_position(end),
+		None => is_position(range),
+	}
+}
+
+/// parse a region like `chr1:100-200`, `chr1:100` or `chr1`.
+/// Positions are 1-based and inclusive, like in samtools and genome browsers
+fn parse_region(region: &str) -> EvalexprResult<Interval> {
+	let invalid = || EvalexprError::CustomMessage(format!("'{region}' is not a valid region"));
+	let position = |p: &str| p.replace(',', "").parse::<i64>().map_err(|_| invalid());
+
+	let (chrom, range) = match region.rsplit_once(':') {
+		Some((chrom, range)) if is_range(range) && !is_known_contig(region) => (chrom, Some(range)),
+		_ => (region, None),
+	};
+
+	let (start, end) = match range.map(|r| r.split_once('-')) {
+		Some(Some((start, end))) => (position(start)?, position(end)?),
+		Some(None) => {
+			let p = position(range.unwrap_or_default())?;
+			(p, p)
+		}
+		// the whole chromosome
+		None => (1, i64::MAX),
+	};
+
+	if chrom.is_empty() || start < 1 || end < start {
+		return Err(invalid());
+	}
+
+	Ok(Interval {
+		chrom: chrom.to_owned(),
+		start: start - 1,
+		end,
+	})
+}
+
+/// get a region from a region string, or a `(chrom, start, end)` tuple in BED coordinates
+fn as_region(value: &Value) -> EvalexprResult<Interval> {
+	match value {
+		Value::String(s) => parse_region(s),
+		Value::Tuple(t) if t.len() == 3 => Ok(Interval {
+			chrom: t[0].as_string()?,
+			start: t[1].as_int()?,
+			end: t[2].as_int()?,
+		}),
+		v => Err(EvalexprError::CustomMessage(format!(
+			"Expected a region like \"chr1:100-200\", got {v}"
+		))),
+	}
+}
+
+/// get the arguments of a function taking `min` to `max` arguments
+fn as_arguments(argument: &Value, min: usize, max: usize) -> EvalexprResult<&[Value]> {
+	match argument {
+		Value::Tuple(t) if (min..=max).contains(&t.len()) => Ok(t.as_slice()),
+		v => Err(EvalexprError::CustomMessage(format!(
+			"Expected {min} to {max} arguments, got {v}"
+		))),
+	}
+}
+
+/// parse the arguments `chrom, start, end, region[, coordinates]`
+fn interval_and_region(argument: &Value) -> EvalexprResult<(Interval, Interval)> {
+	let arguments = as_arguments(argument, 4, 5)?;
+
+	let coordinates = match arguments.get(4) {
+		Some(c) => Coordinates::parse(c)?,
+		None => Coordinates::Bed,
+	};
+	let (start, end) = coordinates.to_bed(arguments[1].as_int()?, arguments[2].as_int()?);
+
+	let interval = Interval {
+		chrom: arguments[0].as_string()?,
+		start,
+		end,
+	};
+
+	Ok((interval, as_region(&arguments[3])?))
+}
+
+/// function comparing an interval with a region using `f`
+fn interval_function(f: fn(&Interval, &Interval) -> Value) -> Function {
+	Function::new(move |argument| {
+		let (interval, region) = interval_and_region(argument)?;
+		Ok(f(&interval, &region))
+	})
+}
+
+/// adds genomic interval functions to `context`.
+/// Intervals are given as chromosome, start and end columns in BED coordinates,
+/// or in 1-based coordinates with a last argument like `"gff"`
+pub fn add_interval_functions(context: &mut HashMapContext) {
+	let functions = [
+		(
+			"overlaps",
+			interval_function(|i, r| Value::Boolean(i.overlaps(r))),
+		),
+		(
+			"within",
+			interval_function(|i, r| Value::Boolean(i.within(r))),
+		),
+		(
+			"distance",
+			interval_function(|i, r| i.distance(r).map_or(Value::Empty, Value::Int)),
+		),
+		(
+			"interval_len",
+			Function::new(|argument| match argument {
+				Value::String(_) => {
+					let region = as_region(argument)?;
+					Ok(Value::Int(region.end - region.start))
+				}
+				_ => {
+					let arguments = as_arguments(argument, 2, 3)?;
+
+					let coordinates = match arguments.get(2) {
+						Some(c) => Coordinates::parse(c)?,
+						None => Coordinates::Bed,
+					};
+					let (start, end) =
+						coordinates.to_bed(arguments[0].as_int()?, arguments[1].as_int()?);
+
+					Ok(Value::Int(end - start))
+				}
+			}),
+		),
+		(
+			"region",
+			Function::new(|argument| {
+				let region = parse_region(&argument.as_string()?)?;
+
+				Ok(Value::Tuple(vec![
+					Value::String(region.chrom),
+					Value::Int(region.start),
+					Value::Int(region.end),
+				]))
+			}),
+		),
+	];
+
+	for (identifier, function) in functions {
+		context
+			.set_function(identifier.to_owned(), function)
+			.unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::functions::create_function_context;
+	use evalexpr::{eval_boolean_with_context, eval_with_context};
+
+	#[test]
+	fn parses_regions() {
+		let interval = |chrom: &str, start, end| Interval {
+			chrom: chrom.to_owned(),
+			start,
+			end,
+		};
+
+		assert_eq!(
+			parse_region("chr1:100-200").unwrap(),
+			interval("chr1", 99, 200)
+		);
+		assert_eq!(
+			parse_region("chr1:1,000-2,000").unwrap(),
+			interval("chr1", 999, 2000)
+		);
+		assert_eq!(parse_region("chrX:5").unwrap(), interval("chrX", 4, 5));
+		assert_eq!(
+			parse_region("HLA-A*01:01").unwrap(),
+			interval("HLA-A*01:01", 0, i64::MAX)
+		);
+		assert_eq!(
+			parse_region("HLA-A*01:01:01:01:100-200").unwrap(),
+			interval("HLA-A*01:01:01:01", 99, 200)
+		);
+		assert_eq!(
+			parse_region("HLA-A*01:01:100-200").unwrap(),
+			interval("HLA-A*01:01", 99, 200)
+		);
+		assert_eq!(
+			parse_region("chrUn:random").unwrap(),
+			interval("chrUn:random", 0, i64::MAX)
+		);
+		assert_eq!(parse_region("chrM").unwrap(), interval("chrM", 0, i64::MAX));
+
+		assert!(parse_region("chr1:200-100").is_err());
+		assert!(parse_region("chr1:0-100").is_err());
+		assert!(parse_region(":1-2").is_err());
+	}
+
+	#[test]
+	fn interval_functions() {
+		let ctx = create_function_context();
+
+		// BED interval 99-100 is the single base 100
+		assert!(
+			eval_boolean_with_context(r#"overlaps("chr1", 99, 100, "chr1:100-200")"#, &ctx)
+				.unwrap()
+		);
+		assert!(
+			!eval_boolean_with_context(r#"overlaps("chr1", 98, 99, "chr1:100-200")"#, &ctx)
+				.unwrap()
+		);
+		assert!(eval_boolean_with_context(
+			r#"overlaps("chr1", 100, 100, "chr1:100-200", "gff")"#,
+			&ctx
+		)
+		.unwrap());
+		assert!(
+			!eval_boolean_with_context(r#"overlaps("chr2", 150, 160, "chr1:100-200")"#, &ctx)
+				.unwrap()
+		);
+
+		assert!(eval_boolean_with_context(
+			r#"within("chr1", 100, 200, "chr1:100-200", "sam")"#,
+			&ctx
+		)
+		.unwrap());
+		assert!(
+			!eval_boolean_with_context(r#"within("chr1", 98, 200, "chr1:100-200")"#, &ctx).unwrap()
+		);
+		assert!(
+			eval_boolean_with_context(r#"within("chr1", 500, 600, region("chr1"))"#, &ctx).unwrap()
+		);
+
+		assert_eq!(
+			eval_with_context(r#"distance("chr1", 250, 300, "chr1:100-200")"#, &ctx).unwrap(),
+			Value::Int(50)
+		);
+		assert_eq!(
+			eval_with_context(r#"distance("chr1", 10, 89, region("chr1:100-200"))"#, &ctx).unwrap(),
+			Value::Int(10)
+		);
+		assert_eq!(
+			eval_with_context(r#"distance("chr1", 150, 160, "chr1:100-200")"#, &ctx).unwrap(),
+			Value::Int(0)
+		);
+
+		assert_eq!(
+			eval_with_context("interval_len(100, 200)", &ctx).unwrap(),
+			Value::Int(100)
+		);
+		assert_eq!(
+			eval_with_context(r#"interval_len(100, 200, "vcf")"#, &ctx).unwrap(),
+			Value::Int(101)
+		);
+		assert_eq!(
+			eval_with_context(r#"interval_len("chr1:100-200")"#, &ctx).unwrap(),
+			Value::Int(101)
+		);
+		assert_eq!(
+			eval_with_context(r#"region("chr1:100-200")"#, &ctx).unwrap(),
+			Value::Tuple(vec![
+				Value::String("chr1".to_owned()),
+				Value::Int(99),
+				Value::Int(200)
+			])
+		);
+
+		// intervals on different chromosomes have no distance
+		assert_eq!(
+			eval_with_context(r#"distance("chr2", 150, 160, "chr1:100-200")"#, &ctx).unwrap(),
+			Value::Empty
+		);
+		assert!(eval_boolean_with_context(
+			r#"is_na(distance("chr2", 150, 160, "chr1:100-200"))"#,
+			&ctx
+		)
+		.unwrap());
+
+		assert!(eval_with_context(r#"overlaps("chr1", 1, 2, "chr1:1-2", "psl")"#, &ctx).is_err());
+	}
+}
@@ -0,0 +1,299 @@
+use evalexpr::{
+	ContextWithMutableFunctions, EvalexprError, EvalexprResult, Function, HashMapContext, Value,
+};
+
+use super::string::resolve_index;
+
+/// get the elements of a list argument
+fn as_list(value: &Value) -> EvalexprResult<&[Value]> {
+	match value {
+		Value::Tuple(t) => Ok(t.as_slice()),
+		v => Err(EvalexprError::expected_tuple(v.to_owned())),
+	}
+}
+
+/// compare `a` and `b` with the comparison operator `op`.
+/// Numbers are compared by value, other values only for equality
+fn compare(a: &Value, op: &str, b: &Value) -> EvalexprResult<bool> {
+	let ordering = match (a, b) {
+		(Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+		(a, b) if a.is_number() && b.is_number() => a.as_number()?.partial_cmp(&b.as_number()?),
+		_ => None,
+	};
+
+	match (op, ordering) {
+		("==", Some(o)) => Ok(o.is_eq()),
+		("!=", Some(o)) => Ok(o.is_ne()),
+		("==", None) => Ok(a == b),
+		("!=", None) => Ok(a != b),
+		("<", Some(o)) => Ok(o.is_lt()),
+		("<=", Some(o)) => Ok(o.is_le()),
+		(">", Some(o)) => Ok(o.is_gt()),
+		(">=", Some(o)) => Ok(o.is_ge()),
+		("<" | "<=" | ">" | ">=", None) => Ok(false),
+		(op, _) => Err(EvalexprError::CustomMessage(format!(
+			"Unknown comparison '{op}'. Expected one of ==, !=, <, <=, >, >="
+		))),
+	}
+}
+
+/// test the elements of a list of booleans for `any`, `all` and `count`
+fn test_elements(argument: &Value) -> EvalexprResult<Vec<bool>> {
+	match argument {
+		// a column range of a single column
+		Value::Boolean(b) => Ok(vec![*b]),
+		v => as_list(v)?.iter().map(|v| v.as_boolean()).collect(),
+	}
+}
+
+/// compare the elements of a list for `any_cmp`, `all_cmp` and `count_cmp`,
+/// called with a list, comparison and value like `any_cmp(c11, ">", 100)`
+fn compare_elements(argument: &Value) -> EvalexprResult<Vec<bool>> {
+	let arguments = argument.as_fixed_len_tuple(3)?;
+	let op = arguments[1].as_string()?;
+
+	// a column range of a single column is a single value
+	let values = match &arguments[0] {
+		Value::Tuple(values) => values.as_slice(),
+		v => std::slice::from_ref(v),
+	};
+
+	values
+		.iter()
+		.map(|v| compare(v, &op, &arguments[2]))
+		.collect()
+}
+
+/// sum the elements of a list, or all arguments.
+/// The result is an int, if all elements are ints and the sum does not overflow
+fn sum(argument: &Value) -> EvalexprResult<Value> {
+	let values = match argument {
+		Value::Tuple(t) => t.as_slice(),
+		v => std::slice::from_ref(v),
+	};
+
+	let ints: Option<Vec<i64>> = values
+		.iter()
+		.map(|v| match v {
+			Value::Int(i) => Some(*i),
+			_ => None,
+		})
+		.collect();
+
+	if let Some(i) = ints.and_then(|ints| ints.into_iter().try_fold(0i64, i64::checked_add)) {
+		return Ok(Value::Int(i));
+	}
+
+	let mut total = 0.0;
+
+	for value in values {
+		total += value.as_number()?;
+	}
+
+	Ok(Value::Float(total))
+}
+
+/// adds list functions to `context`.
+/// `len`, `min` and `max` also accept lists, see the string and math functions
+pub fn add_list_functions(context: &mut HashMapContext) {
+	let functions = [
+		(
+			"any",
+			Function::new(|argument| {
+				Ok(Value::Boolean(
+					test_elements(argument)?.into_iter().any(|r| r),
+				))
+			}),
+		),
+		(
+			"all",
+			Function::new(|argument| {
+				Ok(Value::Boolean(
+					test_elements(argument)?.into_iter().all(|r| r),
+				))
+			}),
+		),
+		(
+			"count",
+			Function::new(|argument| {
+				let passed = test_elements(argument)?.into_iter().filter(|r| *r).count();
+				Ok(Value::Int(passed as i64))
+			}),
+		),
+		(
+			"any_cmp",
+			Function::new(|argument| {
+				Ok(Value::Boolean(
+					compare_elements(argument)?.into_iter().any(|r| r),
+				))
+			}),
+		),
+		(
+			"all_cmp",
+			Function::new(|argument| {
+				Ok(Value::Boolean(
+					compare_elements(argument)?.into_iter().all(|r| r),
+				))
+			}),
+		),
+		(
+			"count_cmp",
+			Function::new(|argument| {
+				let passed = compare_elements(argument)?
+					.into_iter()
+					.filter(|r| *r)
+					.count();
+				Ok(Value::Int(passed as i64))
+			}),
+		),
+		("sum", Function::new(sum)),
+		(
+			"first",
+			Function::new(|argument| match as_list(argument)?.first() {
+				Some(v) => Ok(v.to_owned()),
+				None => Ok(Value::Empty),
+			}),
+		),
+		(
+			"last",
+			Function::new(|argument| match as_list(argument)?.last() {
+				Some(v) => Ok(v.to_owned()),
+				None => Ok(Value::Empty),
+			}),
+		),
+		(
+			"index",
+			Function::new(|argument| {
+				let arguments = argument.as_fixed_len_tuple(2)?;
+				let list = as_list(&arguments[0])?;
+
+				// negative indices count from the end, like in python
+				match resolve_index(arguments[1].as_int()?, list.len()) {
+					Some(i) => Ok(list[i].to_owned()),
+					None => Ok(Value::Empty),
+				}
+			}),
+		),
+	];
+
+	for (identifier, function) in functions {
+		context
+			.set_function(identifier.to_owned(), function)
+			.unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::functions::create_function_context;
+	use evalexpr::{
+		eval_boolean_with_context, eval_with_context, ContextWithMutableVariables, HashMapContext,
+		Value,
+	};
+
+	fn list_context() -> HashMapContext {
+		let mut ctx = create_function_context();
+		let sizes = vec![Value::Int(100), Value::Int(2500), Value::Int(40)];
+
+		ctx.set_value("sizes".to_owned(), Value::Tuple(sizes))
+			.unwrap();
+		ctx.set_value("empty".to_owned(), Value::Tuple(Vec::new()))
+			.unwrap();
+
+		ctx
+	}
+
+	#[test]
+	fn aggregate_functions() {
+		let ctx = list_context();
+
+		assert_eq!(
+			eval_with_context("sum(sizes)", &ctx).unwrap(),
+			Value::Int(2640)
+		);
+		assert_eq!(
+			eval_with_context("sum(1, 2.5)", &ctx).unwrap(),
+			Value::Float(3.5)
+		);
+		assert_eq!(
+			eval_with_context("sum(empty)", &ctx).unwrap(),
+			Value::Int(0)
+		);
+		assert_eq!(
+			eval_with_context("len(sizes)", &ctx).unwrap(),
+			Value::Int(3)
+		);
+		assert_eq!(
+			eval_with_context("len(empty)", &ctx).unwrap(),
+			Value::Int(0)
+		);
+		assert_eq!(
+			eval_with_context("max(sizes)", &ctx).unwrap(),
+			Value::Int(2500)
+		);
+		assert_eq!(
+			eval_with_context("min(sizes)", &ctx).unwrap(),
+			Value::Int(40)
+		);
+		assert!(eval_boolean_with_context("in(2500, sizes)", &ctx).unwrap());
+
+		assert!(eval_with_context(r#"sum(("a", 1))"#, &ctx).is_err());
+	}
+
+	#[test]
+	fn element_functions() {
+		let ctx = list_context();
+
+		assert_eq!(
+			eval_with_context("first(sizes)", &ctx).unwrap(),
+			Value::Int(100)
+		);
+		assert_eq!(
+			eval_with_context("last(sizes)", &ctx).unwrap(),
+			Value::Int(40)
+		);
+		assert_eq!(
+			eval_with_context("index(sizes, 1)", &ctx).unwrap(),
+			Value::Int(2500)
+		);
+		assert_eq!(
+			eval_with_context("index(sizes, -3)", &ctx).unwrap(),
+			Value::Int(100)
+		);
+		assert_eq!(
+			eval_with_context("index(sizes, 3)", &ctx).unwrap(),
+			Value::Empty
+		);
+		assert_eq!(
+			eval_with_context("first(empty)", &ctx).unwrap(),
+			Value::Empty
+		);
+	}
+
+	#[test]
+	fn any_all_functions() {
+		let ctx = list_context();
+
+		assert!(eval_boolean_with_context(r#"any_cmp(sizes, ">", 1000)"#, &ctx).unwrap());
+		assert!(!eval_boolean_with_context(r#"all_cmp(sizes, ">", 1000)"#, &ctx).unwrap());
+		assert!(eval_boolean_with_context(r#"all_cmp(sizes, ">=", 40)"#, &ctx).unwrap());
+		assert!(eval_boolean_with_context(r#"any_cmp(sizes, "==", 40.0)"#, &ctx).unwrap());
+		assert!(eval_boolean_with_context("any((false, true))", &ctx).unwrap());
+		assert!(!eval_boolean_with_context("all((false, true))", &ctx).unwrap());
+		assert!(!eval_boolean_with_context(r#"any_cmp(empty, ">", 0)"#, &ctx).unwrap());
+		assert!(eval_boolean_with_context("all(true)", &ctx).unwrap());
+
+		assert_eq!(
+			eval_with_context(r#"count_cmp(sizes, ">", 50)"#, &ctx).unwrap(),
+			Value::Int(2)
+		);
+		assert_eq!(
+			eval_with_context("count((true, false, true))", &ctx).unwrap(),
+			Value::Int(2)
+		);
+
+		// a list of strings is not a comparison
+		assert!(eval_with_context(r#"any(("a", "b", "c"))"#, &ctx).is_err());
+		assert!(eval_with_context(r#"any_cmp(sizes, "=~", 1)"#, &ctx).is_err());
+	}
+}
@@ -0,0 +1,246 @@
+use evalexpr::{
+	ContextWithMutableFunctions, EvalexprError, EvalexprResult, Function, HashMapContext, Value,
+};
+
+/// convert a rounded float to an int, if it can be represented as one
+fn float_to_int(f: f64) -> Value {
+	if f.is_finite() && f >= i64::MIN as f64 && f <= i64::MAX as f64 {
+		Value::Int(f as i64)
+	} else {
+		Value::Float(f)
+	}
+}
+
+/// function applying `f` to a single numeric argument, always returning a float
+fn float_function(f: fn(f64) -> f64) -> Function {
+	Function::new(move |argument| Ok(Value::Float(f(argument.as_number()?))))
+}
+
+/// function rounding a single numeric argument with `f`.
+/// Ints are returned unchanged
+fn rounding_function(f: fn(f64) -> f64) -> Function {
+	Function::new(move |argument| match argument {
+		Value::Int(i) => Ok(Value::Int(*i)),
+		v => Ok(float_to_int(f(v.as_number()?))),
+	})
+}
+
+/// function testing a single numeric argument with `f`.
+/// Ints are always finite, so `f` is only called for floats
+fn float_predicate(f: fn(f64) -> bool) -> Function {
+	Function::new(move |argument| match argument {
+		Value::Int(_) => Ok(Value::Boolean(false)),
+		v => Ok(Value::Boolean(f(v.as_number()?))),
+	})
+}
+
+/// find the smallest or largest value of all arguments.
+/// The result is an int, if all arguments are ints
+fn extremum(argument: &Value, pick_greater: bool) -> EvalexprResult<Value> {
+	let values = match argument {
+		Value::Tuple(t) => t.as_slice(),
+		v => std::slice::from_ref(v),
+	};
+
+	if values.iter().all(|v| matches!(v, Value::Int(_))) {
+		let ints = values.iter().filter_map(|v| match v {
+			Value::Int(i) => Some(*i),
+			_ => None,
+		});
+		let result = if pick_greater { ints.max() } else { ints.min() };
+
+		if let Some(i) = result {
+			return Ok(Value::Int(i));
+		}
+	}
+
+	// mixing ints and floats results in a float
+	let floats = values
+		.iter()
+		.map(|v| v.as_number())
+		.collect::<EvalexprResult<Vec<f64>>>()?;
+	let result = if pick_greater {
+		floats.into_iter().reduce(f64::max)
+	} else {
+		floats.into_iter().reduce(f64::min)
+	};
+
+	match result {
+		Some(f) => Ok(Value::Float(f)),
+		None => Err(EvalexprError::CustomMessage(
+			"Expected at least one value".to_owned(),
+		)),
+	}
+}
+
+/// adds math functions to `context`
+pub fn add_math_functions(context: &mut HashMapContext) {
+	let functions = [
+		(
+			"abs",
+			Function::new(|argument| match argument {
+				Value::Int(i) => match i.checked_abs() {
+					Some(a) => Ok(Value::Int(a)),
+					None => Ok(Value::Float((*i as f64).abs())),
+				},
+				v => Ok(Value::Float(v.as_number()?.abs())),
+			}),
+		),
+		("sqrt", float_function(f64::sqrt)),
+		("exp", float_function(f64::exp)),
+		("log2", float_function(f64::log2)),
+		("log10", float_function(f64::log10)),
+		(
+			"log",
+			Function::new(|argument| match argument {
+				// natural logarithm, unless a base is given
+				Value::Tuple(_) => {
+					let arguments = argument.as_fixed_len_tuple(2)?;
+					let base = arguments[1].as_number()?;
+
+					Ok(Value::Float(arguments[0].as_number()?.log(base)))
+				}
+				v => Ok(Value::Float(v.as_number()?.ln())),
+			}),
+		),
+		(
+			"pow",
+			Function::new(|argument| {
+				let arguments = argument.as_fixed_len_tuple(2)?;
+
+				match (&arguments[0], &arguments[1]) {
+					(Value::Int(base), Value::Int(exp)) if *exp >= 0 => {
+						match u32::try_from(*exp).ok().and_then(|e| base.checked_pow(e)) {
+							Some(i) => Ok(Value::Int(i)),
+							None => Ok(Value::Float((*base as f64).powf(*exp as f64))),
+						}
+					}
+					(base, exp) => Ok(Value::Float(base.as_number()?.powf(exp.as_number()?))),
+				}
+			}),
+		),
+		("round", rounding_function(f64::round)),
+		("floor", rounding_function(f64::floor)),
+		("ceil", rounding_function(f64::ceil)),
+		("isnan", float_predicate(f64::is_nan)),
+		("isinf", float_predicate(f64::is_infinite)),
+		("min", Function::new(|argument| extremum(argument, false))),
+		("max", Function::new(|argument| extremum(argument, true))),
+	];
+
+	for (identifier, function) in functions {
+		context
+			.set_function(identifier.to_owned(), function)
+			.unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::functions::create_function_context;
+	use evalexpr::{
+		eval_boolean_with_context, eval_float_with_context, eval_int_with_context,
+		eval_with_context, Value,
+	};
+
+	#[test]
+	fn abs_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(eval_int_with_context("abs(5 - 105)", &ctx).unwrap(), 100);
+		assert_eq!(eval_float_with_context("abs(-2.5)", &ctx).unwrap(), 2.5);
+		assert!(eval_boolean_with_context("abs(100 - 250) > 100", &ctx).unwrap());
+
+		assert!(eval_with_context(r#"abs("a")"#, &ctx).is_err());
+	}
+
+	#[test]
+	fn logarithm_functions() {
+		let ctx = create_function_context();
+
+		assert_eq!(eval_float_with_context("log10(1000)", &ctx).unwrap(), 3.0);
+		assert_eq!(eval_float_with_context("log2(8.0)", &ctx).unwrap(), 3.0);
+		assert_eq!(eval_float_with_context("log(1)", &ctx).unwrap(), 0.0);
+		assert_eq!(eval_float_with_context("log(81, 3)", &ctx).unwrap(), 4.0);
+		assert_eq!(eval_float_with_context("sqrt(16)", &ctx).unwrap(), 4.0);
+		assert_eq!(eval_float_with_context("exp(0)", &ctx).unwrap(), 1.0);
+	}
+
+	#[test]
+	fn pow_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_with_context("pow(2, 10)", &ctx).unwrap(),
+			Value::Int(1024)
+		);
+		assert_eq!(
+			eval_with_context("pow(2, -1)", &ctx).unwrap(),
+			Value::Float(0.5)
+		);
+		assert_eq!(
+			eval_with_context("pow(4.0, 0.5)", &ctx).unwrap(),
+			Value::Float(2.0)
+		);
+		assert_eq!(
+			eval_with_context("pow(10, 40)", &ctx).unwrap(),
+			Value::Float(1e40)
+		);
+	}
+
+	#[test]
+	fn rounding_functions() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_with_context("round(2.5)", &ctx).unwrap(),
+			Value::Int(3)
+		);
+		assert_eq!(
+			eval_with_context("floor(2.7)", &ctx).unwrap(),
+			Value::Int(2)
+		);
+		assert_eq!(eval_with_context("ceil(2.1)", &ctx).unwrap(), Value::Int(3));
+		assert_eq!(
+			eval_with_context("floor(-2.1)", &ctx).unwrap(),
+			Value::Int(-3)
+		);
+		assert_eq!(eval_with_context("round(7)", &ctx).unwrap(), Value::Int(7));
+	}
+
+	#[test]
+	fn float_predicates() {
+		let ctx = create_function_context();
+
+		assert!(eval_boolean_with_context("isnan(sqrt(-1))", &ctx).unwrap());
+		assert!(!eval_boolean_with_context("isnan(1.0)", &ctx).unwrap());
+		assert!(!eval_boolean_with_context("isnan(1)", &ctx).unwrap());
+		assert!(eval_boolean_with_context("isinf(log10(0))", &ctx).unwrap());
+		assert!(!eval_boolean_with_context("isinf(5)", &ctx).unwrap());
+	}
+
+	#[test]
+	fn min_max_functions() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_with_context("min(3, 1, 2)", &ctx).unwrap(),
+			Value::Int(1)
+		);
+		assert_eq!(
+			eval_with_context("max(3, 1, 2)", &ctx).unwrap(),
+			Value::Int(3)
+		);
+		assert_eq!(
+			eval_with_context("max(3, 1.5, 2)", &ctx).unwrap(),
+			Value::Float(3.0)
+		);
+		assert_eq!(
+			eval_with_context("min(3, 1.5, 2)", &ctx).unwrap(),
+			Value::Float(1.5)
+		);
+		assert_eq!(eval_with_context("max(4)", &ctx).unwrap(), Value::Int(4));
+
+		assert!(eval_with_context(r#"max(1, "a")"#, &ctx).is_err());
+	}
+}
@@ -1,18 +1,63 @@
 use evalexpr::{context_map, EvalexprError, HashMapContext, Value, ValueType};
 
+mod convert;
+use self::convert::add_conversion_functions;
+
+pub mod date;
+use self::date::add_date_functions;
+
+mod interval;
+use self::interval::add_interval_functions;
+
+mod list;
+use self::list::add_list_functions;
+
+mod math;
+use self::math::add_math_functions;
+
+mod na;
+use self::na::add_na_functions;
+
+mod regex;
+use self::regex::add_regex_functions;
+
+mod string;
+use self::string::add_string_functions;
+
+/// compare values for equality, where ints and floats of the same value are equal
+fn is_equal(a: &Value, b: &Value) -> bool {
+	match (a, b) {
+		(Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+			a.as_number().ok() == b.as_number().ok()
+		}
+		_ => a == b,
+	}
+}
+
 pub fn create_function_context() -> HashMapContext {
-	return context_map! {
+	let mut context = context_map! {
 		"in" => Function::new(|argument| {
 			let arguments = argument.as_fixed_len_tuple(2)?;
 
 			match &arguments[1] {
-				Value::Tuple(tuple) => Ok(Value::Boolean(tuple.contains(&arguments[0]))),
+				Value::Tuple(tuple) => Ok(Value::Boolean(tuple.iter().any(|v| is_equal(v, &arguments[0])))),
 				Value::String(string) => Ok(Value::Boolean(string.contains(&arguments[0].as_string()?))),
 				v => Err(EvalexprError::TypeError { expected: vec![ValueType::Tuple, ValueType::String], actual: v.to_owned() })
 			}
 		})
 	}
 	.unwrap();
+
+	add_conversion_functions(&mut context);
+	add_date_functions(&mut context);
+	add_interval_functions(&mut context);
+	add_list_functions(&mut context);
+	add_math_functions(&mut context);
+	add_na_functions(&mut context);
+	add_regex_functions(&mut context);
+	add_string_functions(&mut context);
+
+	context
 }
 
 #[cfg(test)]
@@ -27,6 +72,7 @@ mod tests {
 		assert!(eval_boolean_with_context(r#"in("a", ("a", "b", "c"))"#, &ctx).unwrap());
 		assert!(eval_boolean_with_context(r#"in(2, (1, 2, 3))"#, &ctx).unwrap());
 		assert!(eval_boolean_with_context(r#"in("foo", (1, 2, "foo", "bar"))"#, &ctx).unwrap());
+		assert!(eval_boolean_with_context(r#"in(2, (1.5, 2.0))"#, &ctx).unwrap());
 
 		assert_eq!(eval_boolean_with_context(r#"in("d", ("a", "b", "c"))"#, &ctx).unwrap(), false);
 		assert_eq!(eval_boolean_with_context(r#"in("2", (1, 2, 3))"#, &ctx).unwrap(), false);
@@ -0,0 +1,76 @@
+use evalexpr::{ContextWithMutableFunctions, Function, HashMapContext, Value};
+
+/// whether `value` is missing: an NA value, a missing column or a float NaN
+fn is_na(value: &Value) -> bool {
+	match value {
+		Value::Empty => true,
+		Value::Float(f) => f.is_nan(),
+		_ => false,
+	}
+}
+
+/// adds functions for missing values to `context`.
+/// Values listed in `--na-values` are empty, like missing columns
+pub fn add_na_functions(context: &mut HashMapContext) {
+	let functions = [
+		(
+			"is_na",
+			Function::new(|argument| Ok(Value::Boolean(is_na(argument)))),
+		),
+		(
+			"coalesce",
+			Function::new(|argument| {
+				let values = match argument {
+					Value::Tuple(t) => t.as_slice(),
+					v => std::slice::from_ref(v),
+				};
+
+				// the first value which is not missing, like in SQL
+				match values.iter().find(|v| !is_na(v)) {
+					Some(v) => Ok(v.to_owned()),
+					None => Ok(Value::Empty),
+				}
+			}),
+		),
+	];
+
+	for (identifier, function) in functions {
+		context
+			.set_function(identifier.to_owned(), function)
+			.unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::functions::create_function_context;
+	use evalexpr::{
+		eval_boolean_with_context, eval_with_context, ContextWithMutableVariables, Value,
+	};
+
+	#[test]
+	fn na_functions() {
+		let mut ctx = create_function_context();
+		ctx.set_value("na".to_owned(), Value::Empty).unwrap();
+		ctx.set_value("nan".to_owned(), Value::Float(f64::NAN))
+			.unwrap();
+
+		assert!(eval_boolean_with_context("is_na(na)", &ctx).unwrap());
+		assert!(eval_boolean_with_context("is_na(nan)", &ctx).unwrap());
+		assert!(!eval_boolean_with_context("is_na(0)", &ctx).unwrap());
+		assert!(!eval_boolean_with_context(r#"is_na("")"#, &ctx).unwrap());
+
+		assert_eq!(
+			eval_with_context("coalesce(na, nan, 5)", &ctx).unwrap(),
+			Value::Int(5)
+		);
+		assert_eq!(
+			eval_with_context("coalesce(1.5, 5)", &ctx).unwrap(),
+			Value::Float(1.5)
+		);
+		assert_eq!(
+			eval_with_context("coalesce(na)", &ctx).unwrap(),
+			Value::Empty
+		);
+	}
+}
@@ -0,0 +1,199 @@
+use evalexpr::{
+	ContextWithMutableFunctions, EvalexprError, EvalexprResult, Function, HashMapContext, Value,
+};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of cached patterns, so patterns varying per line don't fill the memory
+const MAX_CACHED_PATTERNS: usize = 1000;
+
+/// Compiled regular expressions by pattern.
+/// Shared between all functions of a context, so every pattern is only compiled once.
+/// Every thread has its own context, so the lock is not contended
+#[derive(Clone, Default)]
+struct RegexCache(Arc<Mutex<HashMap<String, Arc<Regex>>>>);
+
+impl RegexCache {
+	/// run `f` with the compiled regex for `pattern`.
+	/// The cache is not locked while `f` runs, so threads can match in parallel
+	fn with_regex<T>(&self, pattern: &str, f: impl FnOnce(&Regex) -> T) -> EvalexprResult<T> {
+		let regex = {
+			let mut cache = self.0.lock().unwrap();
+
+			if !cache.contains_key(pattern) {
+				let regex = Regex::new(pattern).map_err(|e| {
+					EvalexprError::CustomMessage(format!(
+						"Invalid regular expression '{pattern}': {e}"
+					))
+				})?;
+
+				// patterns read from columns can be different on every line
+				if cache.len() >= MAX_CACHED_PATTERNS {
+					cache.clear();
+				}
+
+				cache.insert(pattern.to_owned(), Arc::new(regex));
+			}
+
+			// cloning a regex would drop its matching buffers, so it is shared instead
+			Arc::clone(&cache[pattern])
+		};
+
+		Ok(f(&regex))
+	}
+}
+
+/// adds `matches`, `find`, `capture` and `replace` to `context`
+pub fn add_regex_functions(context: &mut HashMapContext) {
+	let cache = RegexCache::default();
+
+	let c = cache.clone();
+	let matches = Function::new(move |argument| {
+		let arguments = argument.as_fixed_len_tuple(2)?;
+		let string = arguments[0].as_string()?;
+
+		c.with_regex(&arguments[1].as_string()?, |r| {
+			Value::Boolean(r.is_match(&string))
+		})
+	});
+
+	let c = cache.clone();
+	let find = Function::new(move |argument| {
+		let arguments = argument.as_fixed_len_tuple(2)?;
+		let string = arguments[0].as_string()?;
+
+		c.with_regex(&arguments[1].as_string()?, |r| match r.find(&string) {
+			Some(m) => Value::String(m.as_str().to_owned()),
+			None => Value::Empty,
+		})
+	});
+
+	let c = cache.clone();
+	let capture = Function::new(move |argument| {
+		let arguments = argument.as_fixed_len_tuple(3)?;
+		let string = arguments[0].as_string()?;
+
+		c.with_regex(&arguments[1].as_string()?, |r| {
+			let captures = match r.captures(&string) {
+				Some(c) => c,
+				None => return Ok(Value::Empty),
+			};
+
+			// groups can be selected by index or by name
+			let group = match &arguments[2] {
+				Value::Int(i) => captures.get(*i as usize),
+				Value::String(name) => captures.name(name),
+				v => return Err(EvalexprError::expected_int(v.to_owned())),
+			};
+
+			Ok(match group {
+				Some(m) => Value::String(m.as_str().to_owned()),
+				None => Value::Empty,
+			})
+		})?
+	});
+
+	let c = cache;
+	let replace = Function::new(move |argument| {
+		let arguments = argument.as_fixed_len_tuple(3)?;
+		let string = arguments[0].as_string()?;
+		let replacement = arguments[2].as_string()?;
+
+		c.with_regex(&arguments[1].as_string()?, |r| {
+			Value::String(r.replace_all(&string, replacement.as_str()).into_owned())
+		})
+	});
+
+	context.set_function("matches".to_owned(), matches).unwrap();
+	context.set_function("find".to_owned(), find).unwrap();
+	context.set_function("capture".to_owned(), capture).unwrap();
+	context.set_function("replace".to_owned(), replace).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::functions::create_function_context;
+	use evalexpr::{eval_boolean_with_context, eval_string_with_context, eval_with_context, Value};
+
+	#[test]
+	fn matches_function() {
+		let ctx = create_function_context();
+
+		assert!(eval_boolean_with_context(r#"matches("NM_005997", "^NM_")"#, &ctx).unwrap());
+		assert!(eval_boolean_with_context(r#"matches("chr22", "[0-9]+$")"#, &ctx).unwrap());
+		assert!(!eval_boolean_with_context(r#"matches("BC007833", "^NM_")"#, &ctx).unwrap());
+
+		assert!(eval_boolean_with_context(r#"matches("abc", "(")"#, &ctx).is_err());
+		assert!(eval_boolean_with_context(r#"matches(1, "1")"#, &ctx).is_err());
+	}
+
+	#[test]
+	fn find_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_string_with_context(r#"find("chr1:100-200", "[0-9]+-[0-9]+")"#, &ctx).unwrap(),
+			"100-200"
+		);
+		assert_eq!(
+			eval_with_context(r#"find("chr1", "[xy]")"#, &ctx).unwrap(),
+			Value::Empty
+		);
+	}
+
+	#[test]
+	fn capture_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_string_with_context(r#"capture("chr1:100-200", "(\\w+):(\\d+)", 2)"#, &ctx)
+				.unwrap(),
+			"100"
+		);
+		assert_eq!(
+			eval_string_with_context(
+				r#"capture("chr1:100-200", "(?P<chrom>\\w+):", "chrom")"#,
+				&ctx
+			)
+			.unwrap(),
+			"chr1"
+		);
+		assert_eq!(
+			eval_with_context(r#"capture("chr1", "(\\d+)-(\\d+)", 1)"#, &ctx).unwrap(),
+			Value::Empty
+		);
+		assert_eq!(
+			eval_with_context(r#"capture("chr1", "chr(\\d+)", 5)"#, &ctx).unwrap(),
+			Value::Empty
+		);
+	}
+
+	#[test]
+	fn replace_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_string_with_context(r#"replace("chr1_random", "_.*$", "")"#, &ctx).unwrap(),
+			"chr1"
+		);
+		assert_eq!(
+			eval_string_with_context(r#"replace("a.b.c", "\\.", "-")"#, &ctx).unwrap(),
+			"a-b-c"
+		);
+	}
+
+	#[test]
+	fn cache_is_limited() {
+		let cache = RegexCache::default();
+
+		for i in 0..MAX_CACHED_PATTERNS + 10 {
+			assert!(cache
+				.with_regex(&format!("^{i}$"), |r| r.is_match(&i.to_string()))
+				.unwrap());
+		}
+
+		assert!(cache.0.lock().unwrap().len() <= MAX_CACHED_PATTERNS);
+	}
+}
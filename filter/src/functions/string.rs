@@ -0,0 +1,251 @@
+use evalexpr::{
+	ContextWithMutableFunctions, EvalexprError, EvalexprResult, Function, HashMapContext, Value,
+	ValueType,
+};
+
+/// split `argument` into its arguments.
+/// Functions called with a single argument receive it directly, instead of as a tuple
+fn arguments(argument: &Value, min: usize, max: usize) -> EvalexprResult<Vec<Value>> {
+	match argument {
+		Value::Tuple(t) if max > 1 => {
+			if (min..=max).contains(&t.len()) {
+				argument.as_tuple()
+			} else {
+				Err(EvalexprError::WrongFunctionArgumentAmount {
+					expected: max,
+					actual: t.len(),
+				})
+			}
+		}
+		_ if min <= 1 => Ok(vec![argument.to_owned()]),
+		v => Err(EvalexprError::expected_tuple(v.to_owned())),
+	}
+}
+
+/// convert a possibly negative `index` into a position in a sequence of length `len`
+pub fn resolve_index(index: i64, len: usize) -> Option<usize> {
+	let i = if index < 0 { len as i64 + index } else { index };
+
+	(0..len as i64).contains(&i).then_some(i as usize)
+}
+
+/// adds string functions to `context`
+pub fn add_string_functions(context: &mut HashMapContext) {
+	let functions = [
+		(
+			"len",
+			Function::new(|argument| match argument {
+				Value::String(s) => Ok(Value::Int(s.chars().count() as i64)),
+				Value::Tuple(t) => Ok(Value::Int(t.len() as i64)),
+				v => Err(EvalexprError::TypeError {
+					expected: vec![ValueType::String, ValueType::Tuple],
+					actual: v.to_owned(),
+				}),
+			}),
+		),
+		(
+			"lower",
+			Function::new(|argument| Ok(Value::String(argument.as_string()?.to_lowercase()))),
+		),
+		(
+			"upper",
+			Function::new(|argument| Ok(Value::String(argument.as_string()?.to_uppercase()))),
+		),
+		(
+			"startswith",
+			Function::new(|argument| {
+				let arguments = argument.as_fixed_len_tuple(2)?;
+				let string = arguments[0].as_string()?;
+
+				Ok(Value::Boolean(
+					string.starts_with(&arguments[1].as_string()?),
+				))
+			}),
+		),
+		(
+			"endswith",
+			Function::new(|argument| {
+				let arguments = argument.as_fixed_len_tuple(2)?;
+				let string = arguments[0].as_string()?;
+
+				Ok(Value::Boolean(string.ends_with(&arguments[1].as_string()?)))
+			}),
+		),
+		(
+			"strip",
+			Function::new(|argument| {
+				let arguments = arguments(argument, 1, 2)?;
+				let string = arguments[0].as_string()?;
+
+				// strip whitespace, unless characters to strip are given
+				let stripped = match arguments.get(1) {
+					Some(chars) => {
+						let chars = chars.as_string()?;
+						string.trim_matches(|c| chars.contains(c)).to_owned()
+					}
+					None => string.trim().to_owned(),
+				};
+
+				Ok(Value::String(stripped))
+			}),
+		),
+		(
+			"substr",
+			Function::new(|argument| {
+				let arguments = arguments(argument, 2, 3)?;
+				let chars: Vec<char> = arguments[0].as_string()?.chars().collect();
+
+				let start = match resolve_index(arguments[1].as_int()?, chars.len()) {
+					Some(s) => s,
+					None => return Ok(Value::String(String::new())),
+				};
+
+				// without a length, the substring continues to the end of the string
+				let end = match arguments.get(2) {
+					Some(len) => (start + len.as_int()?.max(0) as usize).min(chars.len()),
+					None => chars.len(),
+				};
+
+				Ok(Value::String(chars[start..end].iter().collect()))
+			}),
+		),
+		(
+			"split",
+			Function::new(|argument| {
+				let arguments = argument.as_fixed_len_tuple(3)?;
+				let string = arguments[0].as_string()?;
+				let separator = arguments[1].as_string()?;
+				let parts: Vec<&str> = string.split(separator.as_str()).collect();
+
+				match resolve_index(arguments[2].as_int()?, parts.len()) {
+					Some(i) => Ok(Value::String(parts[i].to_owned())),
+					None => Ok(Value::Empty),
+				}
+			}),
+		),
+		(
+			"contains_ci",
+			Function::new(|argument| {
+				let arguments = argument.as_fixed_len_tuple(2)?;
+				let string = arguments[0].as_string()?.to_lowercase();
+				let substring = arguments[1].as_string()?.to_lowercase();
+
+				Ok(Value::Boolean(string.contains(&substring)))
+			}),
+		),
+	];
+
+	for (identifier, function) in functions {
+		context
+			.set_function(identifier.to_owned(), function)
+			.unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::functions::create_function_context;
+	use evalexpr::{
+		eval_boolean_with_context, eval_int_with_context, eval_string_with_context,
+		eval_with_context, Value,
+	};
+
+	#[test]
+	fn len_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_int_with_context(r#"len("CCDS989.1")"#, &ctx).unwrap(),
+			9
+		);
+		assert_eq!(eval_int_with_context(r#"len("")"#, &ctx).unwrap(), 0);
+		assert_eq!(
+			eval_int_with_context(r#"len(("a", "b"))"#, &ctx).unwrap(),
+			2
+		);
+
+		assert!(eval_int_with_context(r#"len(1)"#, &ctx).is_err());
+	}
+
+	#[test]
+	fn case_functions() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_string_with_context(r#"lower("ChrX")"#, &ctx).unwrap(),
+			"chrx"
+		);
+		assert_eq!(
+			eval_string_with_context(r#"upper("ChrX")"#, &ctx).unwrap(),
+			"CHRX"
+		);
+		assert!(eval_boolean_with_context(r#"contains_ci("NM_005997", "nm_")"#, &ctx).unwrap());
+		assert!(!eval_boolean_with_context(r#"contains_ci("NM_005997", "nr_")"#, &ctx).unwrap());
+	}
+
+	#[test]
+	fn prefix_suffix_functions() {
+		let ctx = create_function_context();
+
+		assert!(eval_boolean_with_context(r#"startswith("CCDS989.1", "CCDS")"#, &ctx).unwrap());
+		assert!(!eval_boolean_with_context(r#"startswith("CCDS989.1", "NM")"#, &ctx).unwrap());
+		assert!(eval_boolean_with_context(r#"endswith("chr1_random", "_random")"#, &ctx).unwrap());
+		assert!(!eval_boolean_with_context(r#"endswith("chr1", "_random")"#, &ctx).unwrap());
+
+		assert!(eval_boolean_with_context(r#"startswith("a")"#, &ctx).is_err());
+	}
+
+	#[test]
+	fn strip_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_string_with_context(r#"strip("  chr1 ")"#, &ctx).unwrap(),
+			"chr1"
+		);
+		assert_eq!(
+			eval_string_with_context(r#"strip("--chr1-", "-")"#, &ctx).unwrap(),
+			"chr1"
+		);
+	}
+
+	#[test]
+	fn substr_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_string_with_context(r#"substr("chr22", 3)"#, &ctx).unwrap(),
+			"22"
+		);
+		assert_eq!(
+			eval_string_with_context(r#"substr("chr22", 0, 3)"#, &ctx).unwrap(),
+			"chr"
+		);
+		assert_eq!(
+			eval_string_with_context(r#"substr("chr22", -2, 10)"#, &ctx).unwrap(),
+			"22"
+		);
+		assert_eq!(
+			eval_string_with_context(r#"substr("chr22", 10)"#, &ctx).unwrap(),
+			""
+		);
+	}
+
+	#[test]
+	fn split_function() {
+		let ctx = create_function_context();
+
+		assert_eq!(
+			eval_string_with_context(r#"split("CCDS989.1_cds_0", "_", 1)"#, &ctx).unwrap(),
+			"cds"
+		);
+		assert_eq!(
+			eval_string_with_context(r#"split("CCDS989.1_cds_0", "_", -1)"#, &ctx).unwrap(),
+			"0"
+		);
+		assert_eq!(
+			eval_with_context(r#"split("CCDS989.1_cds_0", "_", 3)"#, &ctx).unwrap(),
+			Value::Empty
+		);
+	}
+}
@@ -0,0 +1,139 @@
+use crate::ColumnType;
+
+/// infer the type of every column in `rows`, the split fields of every line.
+/// Types given in `explicit` take precedence over inferred types.
+/// Values containing `list_separator` are lists
+pub fn infer_column_types(
+	rows: &[Vec<impl AsRef<str>>],
+	explicit: &[ColumnType],
+	list_separator: &str,
+) -> Vec<ColumnType> {
+	let mut types: Vec<ColumnType> = explicit.to_vec();
+
+	for row in rows {
+		for (i, value) in row.iter().map(|v| v.as_ref()).enumerate() {
+			if i < explicit.len() {
+				continue;
+			}
+
+			let value_type = infer_value_type(value, list_separator);
+
+			match types.get_mut(i) {
+				Some(t) => *t = combine_types(t, &value_type),
+				None => {
+					// columns missing on previous lines have no type yet
+					types.resize(i, ColumnType::None);
+					types.push(value_type);
+				}
+			}
+		}
+	}
+
+	types
+}
+
+/// guess the type of a single value
+fn infer_value_type(value: &str, list_separator: &str) -> ColumnType {
+	if value.is_empty() {
+		ColumnType::None
+	} else if value.parse::<i64>().is_ok() {
+		ColumnType::Int
+	} else if value.parse::<f64>().is_ok() {
+		ColumnType::Float
+	} else if value.parse::<bool>().is_ok() {
+		ColumnType::Bool
+	} else if !list_separator.is_empty() && value.contains(list_separator) {
+		let value = value.strip_suffix(list_separator).unwrap_or(value);
+
+		// elements are never lists themselves
+		let element_type = value
+			.split(list_separator)
+			.map(|element| infer_value_type(element, ""))
+			.fold(ColumnType::None, |a, b| combine_types(&a, &b));
+
+		match element_type {
+			ColumnType::None => ColumnType::List(Box::new(ColumnType::Str)),
+			t => ColumnType::List(Box::new(t)),
+		}
+	} else {
+		ColumnType::Str
+	}
+}
+
+/// find the narrowest type both `a` and `b` values can be parsed as
+fn combine_types(a: &ColumnType, b: &ColumnType) -> ColumnType {
+	match (a, b) {
+		(ColumnType::None, t) | (t, ColumnType::None) => t.clone(),
+		(ColumnType::Int, ColumnType::Int) => ColumnType::Int,
+		(ColumnType::Int | ColumnType::Float, ColumnType::Int | ColumnType::Float) => {
+			ColumnType::Float
+		}
+		(ColumnType::Bool, ColumnType::Bool) => ColumnType::Bool,
+		(ColumnType::List(a), ColumnType::List(b)) => {
+			ColumnType::List(Box::new(combine_types(a, b)))
+		}
+		// single numbers are lists with one element, text is not
+		(ColumnType::List(a), b @ (ColumnType::Int | ColumnType::Float | ColumnType::Bool))
+		| (b @ (ColumnType::Int | ColumnType::Float | ColumnType::Bool), ColumnType::List(a)) => {
+			ColumnType::List(Box::new(combine_types(a, b)))
+		}
+		_ => ColumnType::Str,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn names(types: &[ColumnType]) -> Vec<String> {
+		types.iter().map(|t| t.to_string()).collect()
+	}
+
+	fn rows<'a>(lines: &[&'a str]) -> Vec<Vec<&'a str>> {
+		lines.iter().map(|l| l.split('\t').collect()).collect()
+	}
+
+	#[test]
+	fn infers_single_values() {
+		let lines = ["chr1\t100\t0.5\ttrue\t1,2,3\t"];
+
+		assert_eq!(
+			names(&infer_column_types(&rows(&lines), &[], ",")),
+			vec!["str", "int", "float", "bool", "list:int", "none"]
+		);
+	}
+
+	#[test]
+	fn widens_across_lines() {
+		let lines = ["1\t1\ttrue\t1,2\t", "2\t1.5\tfoo\tbar\t3", "3"];
+
+		assert_eq!(
+			names(&infer_column_types(&rows(&lines), &[], ",")),
+			vec!["int", "float", "str", "str", "int"]
+		);
+	}
+
+	#[test]
+	fn explicit_types_take_precedence() {
+		let lines = ["1\t2\t3"];
+
+		assert_eq!(
+			names(&infer_column_types(&rows(&lines), &[ColumnType::Str], ",")),
+			vec!["str", "int", "int"]
+		);
+	}
+
+	#[test]
+	fn infers_list_elements() {
+		let lines = ["1,2,\ta;b\t1.5,2", "3\tc;d\t3,4"];
+
+		assert_eq!(
+			names(&infer_column_types(&rows(&lines), &[], ",")),
+			vec!["list:int", "str", "list:float"]
+		);
+		assert_eq!(
+			names(&infer_column_types(&rows(&lines), &[], ";")),
+			vec!["str", "list", "str"]
+		);
+	}
+}
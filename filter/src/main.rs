@@ -1,28 +1,55 @@
 use anyhow::anyhow;
 use clap::{Parser, ValueEnum};
-use evalexpr::{build_operator_tree, ContextWithMutableVariables, HashMapContext, Node, Value};
-use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use evalexpr::{build_operator_tree, Node, Value};
+use io_utils::{output_ending, Compression, Delimiter, LineBuffer, Reader, Writer};
+use std::borrow::Cow;
+use std::io::{BufRead, Write};
+use std::ops::Range;
 use std::process::exit;
+use std::time::Instant;
+
+mod context;
+use crate::context::LineContext;
+
+mod explain;
+use crate::explain::explain_expressions;
 
 mod functions;
-use crate::functions::create_function_context;
+use crate::functions::date::{
+	format_date, is_parsable_format, parse_date, parse_datetime, DATE_FORMAT,
+};
+
+mod inference;
+use crate::inference::infer_column_types;
+
+mod missing;
+use crate::missing::eval_with_missing_values;
+
+mod python;
+use crate::python::translate_python;
+
+mod report;
+use crate::report::{FilterReport, InferredType, OutputReport};
 
 #[cfg(test)]
 mod tests;
 
-#[derive(Parser)]
+#[derive(Parser, Default)]
 pub struct Arguments {
-	/// File to be filtered
+	/// File to be filtered. Use `-` to read from stdin
 	#[arg(short, long)]
 	in_file: String,
 
-	/// File to write to
+	/// File to write to. Use `-` to write to stdout
 	#[arg(short, long)]
 	out_file: String,
 
-	/// Expression used to filter rows
+	/// Expression used to filter rows.
+	/// Columns are available as `c1`, `c2`, ..., the line number as `NR`,
+	/// the number of fields as `NF` and the whole line as `line`.
+	/// Column ranges like `c4..c9` in the argument of a list function like `any`, `all`, `count`,
+	/// `sum`, `min` or `max` are a list with one value per column,
+	/// e.g. `any(c4..c9 > 5)`, `any(log10(c4..c9) > 2)` or `count(c4..c9 != "") >= 3`
 	#[arg(short, long)]
 	expression: String,
 
@@ -30,25 +57,362 @@ pub struct Arguments {
 	#[arg(short, long, default_value_t = 0)]
 	skip_lines: usize,
 
-	/// Comma-separated list of column types
+	/// Comma-separated list of column types: str, int, float, bool, none, list, date or datetime.
+	/// The elements of lists are strings, unless their type is given like `list:int`.
+	/// Dates are parsed as ISO 8601, unless a format is given like `date:%d.%m.%Y`.
+	/// The last type can be repeated for all remaining columns, like `str,int,float*`
 	#[arg(short, long, value_parser = clap::value_parser!(ColumnType), num_args = 1.., value_delimiter = ',')]
 	types: Vec<ColumnType>,
+
+	/// Separator between the elements of list columns. Defaults to `,`
+	#[arg(long, value_parser = clap::builder::NonEmptyStringValueParser::new())]
+	list_separator: Option<String>,
+
+	/// Treat the first line after the skipped lines as header.
+	/// Its column names can be used as variables in the expression.
+	/// Names containing spaces or other special characters can be quoted with backticks
+	#[arg(long)]
+	header: bool,
+
+	/// Number of data lines used to infer the types of columns not listed in `types`.
+	/// Set to 0 to disable type inference
+	#[arg(long, default_value_t = 100)]
+	infer_lines: usize,
+
+	/// Write comment lines to the output, instead of skipping them
+	#[arg(long)]
+	keep_comments: bool,
+
+	/// Comma-separated list of prefixes marking comment lines. Defaults to `#`
+	#[arg(long, num_args = 1.., value_delimiter = ',')]
+	comment_prefixes: Option<Vec<String>>,
+
+	/// Comma-separated list of values marking missing values, like `.,NA,NaN,-`.
+	/// They are empty in the expression, see `is_na(c1)` and `coalesce(c1, 0)`
+	#[arg(long, num_args = 1.., value_delimiter = ',')]
+	na_values: Option<Vec<String>>,
+
+	/// Whether expressions with comparisons of missing values are false, or make the line invalid.
+	/// Comparisons with a missing value are unknown, like in SQL,
+	/// so `is_na(c1) || c1 > 5` is true and `c1 > 5 && c2 > 5` false if `c2` is at most 5
+	#[arg(long, value_enum, default_value_t = NaComparisons::Invalid)]
+	na_comparisons: NaComparisons,
+
+	/// Number of threads evaluating the expression.
+	/// The output is the same for any number of threads
+	#[arg(long, default_value_t = 1)]
+	threads: usize,
+
+	/// Syntax of the expression. `python` accepts the expressions of Galaxy's original Filter1 tool
+	#[arg(long, value_enum, default_value_t = ExpressionSyntax::Native)]
+	syntax: ExpressionSyntax,
+
+	/// File to write lines not passing the expression to.
+	/// When partitioning, lines passing none of the expressions are written to it
+	#[arg(long, visible_alias = "else-file")]
+	rejected_file: Option<String>,
+
+	/// File to write invalid lines to, annotated with their line number, starting at 1, and reason
+	#[arg(long)]
+	invalid_file: Option<String>,
+
+	/// Additional outputs in the format `expression=file`.
+	/// Every line is written to the output of the first expression it passes,
+	/// starting with `expression` and `out_file`
+	#[arg(long, value_parser = parse_partition, num_args = 1..)]
+	partition: Vec<(String, String)>,
+
+	/// Encoding used to decode column values for the expression.
+	/// Lines are written to the outputs unchanged, regardless of the encoding
+	#[arg(long, value_enum, default_value_t = Encoding::Utf8)]
+	encoding: Encoding,
+
+	/// Compression of the output files. By default it is selected by their extension.
+	/// Compressed input files are detected automatically
+	#[arg(long, value_enum)]
+	compression: Option<Compression>,
+
+	/// Write all lines with LF line endings.
+	/// By default, lines keep their original line ending.
+	/// A missing final newline is only missing in the output of the last line,
+	/// other outputs end with a newline, like the outputs of Galaxy's Filter1 tool
+	#[arg(long)]
+	normalize_eol: bool,
+
+	/// Separator between columns: `tab`, `comma` (with quoted fields as in CSV files),
+	/// `whitespace` or any single character. Lines are written unchanged, keeping the delimiter
+	#[arg(long, default_value = "tab")]
+	delimiter: Delimiter,
+
+	/// File to write the counts of the run to, as JSON
+	#[arg(long)]
+	report_json: Option<String>,
+
+	/// Print the parsed expression and the columns it uses,
+	/// and evaluate it for the given number of data lines (5 by default) instead of filtering
+	#[arg(long, num_args = 0..=1, default_missing_value = "5")]
+	explain: Option<usize>,
 }
 
-#[derive(Clone, ValueEnum)]
+/// parse a partition argument in the format `expression=file`
+fn parse_partition(arg: &str) -> Result<(String, String), anyhow::Error> {
+	// expressions may contain `=` themselves, so split at the last one
+	match arg.rsplit_once('=') {
+		Some((expression, file)) if !expression.is_empty() && !file.is_empty() => {
+			Ok((expression.to_owned(), file.to_owned()))
+		}
+		_ => Err(anyhow!(
+			"Expected partition in the format 'expression=file', got '{arg}'"
+		)),
+	}
+}
+
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ExpressionSyntax {
+	#[default]
+	Native,
+	Python,
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum ColumnType {
 	Str,
 	Int,
 	Float,
 	Bool,
 	None,
-	List,
+	/// List of elements of the given type
+	List(Box<ColumnType>),
+	/// Date with an optional format, as seconds since 1970-01-01
+	Date(Option<String>),
+	/// Date and time with an optional format, as seconds since 1970-01-01 UTC
+	DateTime(Option<String>),
+	/// Type of the last declared column and all following columns
+	Repeated(Box<ColumnType>),
+}
+
+impl std::str::FromStr for ColumnType {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Some(t) = s.strip_suffix('*') {
+			return match ColumnType::from_str(t)? {
+				ColumnType::Repeated(_) => {
+					Err(format!("Expected a single * after the type, got '{s}'"))
+				}
+				t => Ok(ColumnType::Repeated(Box::new(t))),
+			};
+		}
+
+		match s {
+			"str" => Ok(ColumnType::Str),
+			"int" => Ok(ColumnType::Int),
+			"float" => Ok(ColumnType::Float),
+			"bool" => Ok(ColumnType::Bool),
+			"none" => Ok(ColumnType::None),
+			"list" => Ok(ColumnType::List(Box::new(ColumnType::Str))),
+			"date" => Ok(ColumnType::Date(None)),
+			"datetime" => Ok(ColumnType::DateTime(None)),
+			_ if s.starts_with("date:") => {
+				date_format(&s[5..], parse_date).map(|f| ColumnType::Date(Some(f)))
+			}
+			_ if s.starts_with("datetime:") => {
+				date_format(&s[9..], parse_datetime).map(|f| ColumnType::DateTime(Some(f)))
+			}
+			_ => match s.strip_prefix("list:").map(ColumnType::from_str) {
+				Some(Ok(
+					t @ (ColumnType::Str | ColumnType::Int | ColumnType::Float | ColumnType::Bool),
+				)) => Ok(ColumnType::List(Box::new(t))),
+				_ => Err(format!(
+					"Expected str, int, float, bool, none, list, list:<str|int|float|bool>, date, date:<format>, datetime or datetime:<format>, got '{s}'"
+				)),
+			},
+		}
+	}
+}
+
+/// check that `format` of a date or datetime column can be used to parse its values
+fn date_format(
+	format: &str,
+	parse: fn(&str, Option<&str>) -> Option<i64>,
+) -> Result<String, String> {
+	if is_parsable_format(format, parse) {
+		Ok(format.to_owned())
+	} else {
+		Err(format!(
+			"'{format}' can not be used to parse dates, it must contain a full date like %d.%m.%Y, and for datetimes a time like %H:%M"
+		))
+	}
+}
+
+/// How expressions unknown because of missing values, like `c1 > 5` or `c1 != 5` with `c1` NA,
+/// are evaluated
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum NaComparisons {
+	/// The line is invalid
+	#[default]
+	Invalid,
+	/// The expression is false
+	False,
+}
+
+/// Text encoding of the input
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Encoding {
+	/// UTF-8, replacing invalid bytes with U+FFFD
+	#[default]
+	Utf8,
+	/// ISO-8859-1
+	Latin1,
+}
+
+impl Encoding {
+	/// decode `bytes` to a string
+	fn decode<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+		match self {
+			Encoding::Utf8 => String::from_utf8_lossy(bytes),
+			Encoding::Latin1 => match std::str::from_utf8(bytes) {
+				// ascii is the same in both encodings
+				Ok(s) if s.is_ascii() => Cow::Borrowed(s),
+				_ => Cow::Owned(bytes.iter().map(|&b| b as char).collect()),
+			},
+		}
+	}
+}
+
+impl std::fmt::Display for ColumnType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ColumnType::Str => write!(f, "str"),
+			ColumnType::Int => write!(f, "int"),
+			ColumnType::Float => write!(f, "float"),
+			ColumnType::Bool => write!(f, "bool"),
+			ColumnType::None => write!(f, "none"),
+			ColumnType::List(t) if **t == ColumnType::Str => write!(f, "list"),
+			ColumnType::List(t) => write!(f, "list:{t}"),
+			ColumnType::Date(None) => write!(f, "date"),
+			ColumnType::Date(Some(format)) => write!(f, "date:{format}"),
+			ColumnType::DateTime(None) => write!(f, "datetime"),
+			ColumnType::DateTime(Some(format)) => write!(f, "datetime:{format}"),
+			ColumnType::Repeated(t) => write!(f, "{t}*"),
+		}
+	}
+}
+
+/// Options for `filter_with_expression`
+#[derive(Clone)]
+pub struct FilterOptions {
+	/// Number of header lines to skip
+	pub skip_lines: usize,
+
+	/// Types of the columns. Missing types are inferred
+	pub column_types: Vec<ColumnType>,
+
+	/// Whether the first line after the skipped lines is a header
+	pub header: bool,
+
+	/// Number of data lines to infer column types from
+	pub infer_lines: usize,
+
+	/// Whether comment lines are written to the output
+	pub keep_comments: bool,
+
+	/// Prefixes marking comment lines
+	pub comment_prefixes: Vec<String>,
+
+	/// Number of threads evaluating the expression
+	pub threads: usize,
+
+	/// Encoding used to decode column values
+	pub encoding: Encoding,
+
+	/// Whether all lines are written with LF line endings
+	pub normalize_eol: bool,
+
+	/// Separator between columns
+	pub delimiter: Delimiter,
+
+	/// Separator between the elements of list columns
+	pub list_separator: String,
+
+	/// Values marking missing values
+	pub na_values: Vec<String>,
+
+	/// How expressions unknown because of missing values are evaluated
+	pub na_comparisons: NaComparisons,
+}
+
+impl FilterOptions {
+	/// check whether `line` starts with one of the comment prefixes
+	fn is_comment(&self, line: &[u8]) -> bool {
+		self.comment_prefixes
+			.iter()
+			.any(|p| line.starts_with(p.as_bytes()))
+	}
+
+	/// check whether `line` is neither blank nor a comment
+	fn is_data_line(&self, line: &[u8]) -> bool {
+		!(line.iter().all(u8::is_ascii_whitespace) || self.is_comment(line))
+	}
+
+	/// check whether `value` is one of the values marking missing values
+	fn is_na(&self, value: &str) -> bool {
+		self.na_values.iter().any(|na| na == value)
+	}
+}
+
+impl Default for FilterOptions {
+	fn default() -> Self {
+		FilterOptions {
+			skip_lines: 0,
+			column_types: Vec::new(),
+			header: false,
+			infer_lines: 0,
+			keep_comments: false,
+			comment_prefixes: vec!["#".to_owned()],
+			threads: 1,
+			encoding: Encoding::Utf8,
+			normalize_eol: false,
+			delimiter: Delimiter::Tab,
+			list_separator: ",".to_owned(),
+			na_values: Vec::new(),
+			na_comparisons: NaComparisons::Invalid,
+		}
+	}
+}
+
+impl From<&Arguments> for FilterOptions {
+	fn from(args: &Arguments) -> Self {
+		FilterOptions {
+			skip_lines: args.skip_lines,
+			column_types: args.types.clone(),
+			header: args.header,
+			infer_lines: args.infer_lines,
+			keep_comments: args.keep_comments,
+			comment_prefixes: match &args.comment_prefixes {
+				Some(prefixes) => prefixes.clone(),
+				None => FilterOptions::default().comment_prefixes,
+			},
+			threads: args.threads,
+			encoding: args.encoding,
+			normalize_eol: args.normalize_eol,
+			delimiter: args.delimiter,
+			list_separator: match &args.list_separator {
+				Some(separator) => separator.clone(),
+				None => FilterOptions::default().list_separator,
+			},
+			na_values: args.na_values.clone().unwrap_or_default(),
+			na_comparisons: args.na_comparisons,
+		}
+	}
 }
 
 fn main() {
 	let args = Arguments::parse();
 
 	match run_with_args(&args) {
+		// the report must not end up in the output
+		Ok(report) if args.writes_to_stdout() => eprint!("{report}"),
 		Ok(report) => print!("{report}"),
 		Err(e) => {
 			eprintln!("{e}");
@@ -57,51 +421,236 @@ fn main() {
 	};
 }
 
+impl Arguments {
+	/// check whether any of the outputs is stdout
+	fn writes_to_stdout(&self) -> bool {
+		[&self.out_file]
+			.into_iter()
+			.chain(self.partition.iter().map(|(_, file)| file))
+			.chain(&self.rejected_file)
+			.chain(&self.invalid_file)
+			.any(|file| io_utils::is_stdio(file))
+	}
+}
+
 pub fn run_with_args(args: &Arguments) -> Result<String, anyhow::Error> {
+	let translate = |expression: &String| match args.syntax {
+		ExpressionSyntax::Native => Ok(expression.clone()),
+		ExpressionSyntax::Python => translate_python(expression),
+	};
+
+	let mut outputs = vec![(translate(&args.expression)?, &args.out_file)];
+
+	for (expression, file) in &args.partition {
+		outputs.push((translate(expression)?, file));
+	}
+
 	let mut reader = create_reader(&args.in_file)?;
-	let mut writer = create_writer(&args.out_file)?;
 
-	let res = filter_with_expression(
+	if let Some(lines) = args.explain {
+		let expressions: Vec<String> = outputs.into_iter().map(|(e, _)| e).collect();
+		return explain_expressions(&mut reader, &expressions, &FilterOptions::from(args), lines);
+	}
+	let mut writers = Vec::new();
+
+	for (_, file) in &outputs {
+		writers.push(create_writer(file, args.compression)?);
+	}
+
+	let create_optional_writer = |file: &Option<String>| {
+		file.as_ref()
+			.map(|f| create_writer(f, args.compression))
+			.transpose()
+	};
+
+	let mut rejected_writer = create_optional_writer(&args.rejected_file)?;
+	let mut invalid_writer = create_optional_writer(&args.invalid_file)?;
+
+	let mut partitions: Vec<Partition> = outputs
+		.iter()
+		.zip(writers.iter_mut())
+		.map(|((expression, file), writer)| Partition {
+			name: file.to_string(),
+			expression: expression.to_owned(),
+			writer,
+		})
+		.collect();
+
+	let report = partition_with_expressions(
 		&mut reader,
-		&mut writer,
-		&args.expression,
-		args.skip_lines,
-		&args.types,
-	);
+		&mut partitions,
+		&FilterOptions::from(args),
+		rejected_writer.as_mut(),
+		invalid_writer.as_mut(),
+	)?;
+
+	drop(partitions);
+
+	// compressed outputs are only complete once finished
+	for writer in writers
+		.into_iter()
+		.chain(rejected_writer)
+		.chain(invalid_writer)
+	{
+		match io_utils::finish(writer) {
+			Ok(_) => (),
+			Err(_e) => return Err(anyhow!("Failed to write to output file")),
+		};
+	}
 
-	res
+	if let Some(file) = &args.report_json {
+		match io_utils::write_json_report(file, &report) {
+			Ok(_) => (),
+			Err(_e) => return Err(anyhow!("Failed to write report to '{file}'")),
+		};
+	}
+
+	Ok(report.to_string())
 }
 
-/// create a buffered reader from `file path`
-pub fn create_reader(file_path: &String) -> Result<BufReader<File>, anyhow::Error> {
-	match File::open(file_path) {
-		Ok(f) => Ok(BufReader::new(f)),
+/// create a buffered reader from `file path`, decompressing compressed files
+pub fn create_reader(file_path: &String) -> Result<Reader, anyhow::Error> {
+	match io_utils::open(file_path) {
+		Ok(r) => Ok(r),
 		Err(_) => Err(anyhow!("Failed to open input file '{file_path}'")),
 	}
 }
 
-/// create a buffered writer from `file_path`
-pub fn create_writer(file_path: &String) -> Result<BufWriter<File>, anyhow::Error> {
-	match File::create(file_path) {
-		Ok(f) => Ok(BufWriter::new(f)),
+/// create a buffered writer from `file_path`, compressed with `compression`
+pub fn create_writer(
+	file_path: &String,
+	compression: Option<Compression>,
+) -> Result<Writer, anyhow::Error> {
+	match io_utils::create(file_path, compression) {
+		Ok(w) => Ok(w),
 		Err(_) => Err(anyhow!("Failed to create output file '{file_path}'")),
 	}
 }
 
-/// filter `input_reader` to `output_reader` using `expression`
+/// write `line` to `writer`, followed by `ending`.
+/// Only the last line of the input can have no ending, so outputs not receiving it end with a newline
+fn write_line(
+	writer: &mut Writer,
+	line: &[u8],
+	ending: &[u8],
+	line_number: usize,
+) -> Result<(), anyhow::Error> {
+	match writer
+		.write_all(line)
+		.and_then(|_| writer.write_all(ending))
+	{
+		Ok(_) => Ok(()),
+		Err(_) => Err(anyhow!(
+			"Failed to write to output file at line number {line_number}"
+		)),
+	}
+}
+
+/// filter `input_reader` to `output_reader` using `expression`.
+/// Lines not passing the expression are written to `rejected_writer`,
+/// and invalid lines to `invalid_writer`, if given
 pub fn filter_with_expression(
-	input_reader: &mut BufReader<File>,
-	output_writer: &mut BufWriter<File>,
+	input_reader: &mut impl BufRead,
+	output_writer: &mut Writer,
 	expression: &String,
-	skip_lines: usize,
-	column_types: &[ColumnType],
-) -> Result<String, anyhow::Error> {
-	let columns = get_used_columns(expression);
+	options: &FilterOptions,
+	rejected_writer: Option<&mut Writer>,
+	invalid_writer: Option<&mut Writer>,
+) -> Result<FilterReport, anyhow::Error> {
+	let mut partitions = [Partition {
+		name: String::new(),
+		expression: expression.to_owned(),
+		writer: output_writer,
+	}];
 
-	let precompiled_exp = match compile_expression(&columns, column_types, expression) {
-		Ok(n) => n,
-		Err(e) => return Err(e),
-	};
+	partition_with_expressions(
+		input_reader,
+		&mut partitions,
+		options,
+		rejected_writer,
+		invalid_writer,
+	)
+}
+
+/// Number of lines read at once for every thread evaluating the expressions
+const LINES_PER_THREAD: usize = 16384;
+
+/// An output receiving the lines passing `expression`
+pub struct Partition<'a> {
+	/// Name of the output in the report
+	pub name: String,
+	pub expression: String,
+	pub writer: &'a mut Writer,
+}
+
+/// split `input_reader` into `partitions` in a single pass.
+/// Every line is written to the first partition whose expression it passes.
+/// Lines passing no expression are written to `rejected_writer`,
+/// and invalid lines to `invalid_writer`, if given
+pub fn partition_with_expressions(
+	input_reader: &mut impl BufRead,
+	partitions: &mut [Partition],
+	options: &FilterOptions,
+	mut rejected_writer: Option<&mut Writer>,
+	mut invalid_writer: Option<&mut Writer>,
+) -> Result<FilterReport, anyhow::Error> {
+	let start = Instant::now();
+
+	let FilterOptions {
+		infer_lines,
+		keep_comments,
+		threads,
+		encoding,
+		normalize_eol,
+		..
+	} = *options;
+
+	// amount of read lines. Including skipped and invalid
+	let mut total_lines: usize = 0;
+
+	let mut lines_kept: usize = 0;
+
+	let is_data_line = |line: &[u8]| options.is_data_line(line);
+
+	// all lines are read into the same buffer, to avoid allocating every line
+	let mut buffer = LineBuffer::default();
+
+	// skipped lines and the header line are copied to the output unchanged
+	let header_names = read_header(&mut buffer, input_reader, options)?;
+
+	for (line_number, line, ending) in buffer.iter_with_endings() {
+		let ending = output_ending(ending, normalize_eol);
+		total_lines += 1;
+
+		for partition in partitions.iter_mut() {
+			write_line(partition.writer, line, ending, line_number)?;
+		}
+
+		if let Some(w) = rejected_writer.as_deref_mut() {
+			write_line(w, line, ending, line_number)?;
+		}
+
+		lines_kept += 1;
+	}
+
+	buffer.clear();
+
+	// data lines are read ahead to infer column types.
+	// They stay in the buffer, to be filtered first
+	read_data_lines(&mut buffer, input_reader, options, infer_lines)?;
+
+	let expressions: Vec<String> = partitions.iter().map(|p| p.expression.clone()).collect();
+	let CompiledExpressions {
+		column_types,
+		declared_types,
+		expressions,
+		columns,
+		line_variables,
+	} = compile_expressions(&expressions, &buffer, &header_names, options)?;
+	let column_types = &column_types;
+
+	let mut partition_lines = vec![0; partitions.len()];
+	let mut rejected_lines: usize = 0;
 
 	// Lines with invalid contents
 	let mut invalid_lines: usize = 0;
@@ -111,120 +660,462 @@ pub fn filter_with_expression(
 	// empty or comment lines
 	let mut skipped_lines: usize = 0;
 
-	// amount of read lines. Including skipped and invalid
-	let mut total_lines: usize = 0;
+	// comment lines written to the output
+	let mut comment_lines: usize = 0;
 
-	let mut lines_kept: usize = 0;
+	// every thread evaluates the expressions with its own context.
+	// Contexts are not cloned, so they don't share their regex cache
+	let mut contexts: Vec<LineContext> = (0..threads.max(1)).map(|_| LineContext::new()).collect();
 
-	let mut ctx = create_function_context();
+	let evaluate = |line_number: usize, line: &[u8], ctx: &mut LineContext| {
+		if !is_data_line(line) {
+			return Ok(None);
+		}
 
-	for (line_number, line) in input_reader.lines().enumerate() {
-		let line = match line {
-			Ok(l) => l,
-			Err(_) => return Err(anyhow!("Failed to read file at line number {line_number}")),
-		};
+		let na_columns = mutate_context_for_line(
+			line,
+			line_number,
+			column_types,
+			&columns,
+			line_variables,
+			options,
+			ctx,
+		)?;
 
-		// convert given line to buffer with newline
-		let buf = |line: String| format!("{line}\n").into_bytes();
+		first_passed(&expressions, ctx, &na_columns, options.na_comparisons)
+	};
 
-		total_lines += 1;
+	let batch_lines = contexts.len() * LINES_PER_THREAD;
 
-		if line_number < skip_lines {
-			match output_writer.write(&buf(line)) {
-				Ok(_) => lines_kept += 1,
-				Err(_) => {
-					return Err(anyhow!(
-						"Failed to write to output file at line number {line_number}"
-					))
-				}
-			};
-			continue;
-		}
+	loop {
+		while buffer.len() < batch_lines && read_line(&mut buffer, input_reader)?.is_some() {}
 
-		if line.trim().is_empty() || line.starts_with('#') {
-			skipped_lines += 1;
-			continue;
+		if buffer.is_empty() {
+			break;
 		}
 
-		let mut line_invalid = || {
-			if invalid_lines == 0 {
-				first_invalid_line = line_number;
-				invalid_line_content = line.clone();
-			}
+		let results = evaluate_in_parallel(&buffer, &mut contexts, &evaluate);
 
-			invalid_lines += 1;
-		};
+		for ((line_number, line, ending), passed) in buffer.iter_with_endings().zip(results) {
+			let ending = output_ending(ending, normalize_eol);
+			total_lines += 1;
 
-		match mutate_context_for_line(&line, column_types, &columns, &mut ctx) {
-			Ok(_) => (),
-			Err(_) => {
-				line_invalid();
+			if keep_comments && options.is_comment(line) {
+				for partition in partitions.iter_mut() {
+					write_line(partition.writer, line, ending, line_number)?;
+				}
+
+				if let Some(w) = rejected_writer.as_deref_mut() {
+					write_line(w, line, ending, line_number)?;
+				}
+
+				comment_lines += 1;
 				continue;
 			}
-		};
 
-		let passed = match precompiled_exp.eval_boolean_with_context(&ctx) {
-			Ok(b) => b,
-			Err(_) => {
-				line_invalid();
+			if !is_data_line(line) {
+				skipped_lines += 1;
 				continue;
 			}
-		};
 
-		if passed {
-			match output_writer.write(&buf(line)) {
-				Ok(_) => lines_kept += 1,
-				Err(_) => {
-					return Err(anyhow!(
-						"Failed to write to output file at line number {line_number}",
-					))
+			match passed {
+				Ok(Some(i)) => {
+					write_line(partitions[i].writer, line, ending, line_number)?;
+					partition_lines[i] += 1;
+					lines_kept += 1;
 				}
-			};
+				Ok(None) => {
+					rejected_lines += 1;
+
+					if let Some(w) = rejected_writer.as_deref_mut() {
+						write_line(w, line, ending, line_number)?;
+					}
+				}
+				Err(e) => {
+					if invalid_lines == 0 {
+						first_invalid_line = line_number;
+						invalid_line_content = encoding.decode(line).into_owned();
+					}
+
+					invalid_lines += 1;
+
+					if let Some(w) = invalid_writer.as_deref_mut() {
+						// keep the annotation within one tab-separated line
+						let reason = e.to_string().replace(['\t', '\n'], " ");
+						// line numbers are 0 based internally, but 1 based like NR in the file
+						let mut annotated = format!("{}\t{reason}\t", line_number + 1).into_bytes();
+						annotated.extend_from_slice(line);
+
+						write_line(w, &annotated, ending, line_number)?;
+					}
+				}
+			}
 		}
+
+		buffer.clear();
 	}
 
-	// make sure output buffer is written to disk
-	match output_writer.flush() {
-		Ok(_) => (),
-		Err(_e) => return Err(anyhow!("Failed to write to output file")),
+	// make sure output buffers are written to disk
+	let writers = partitions
+		.iter_mut()
+		.map(|p| &mut *p.writer)
+		.chain(rejected_writer)
+		.chain(invalid_writer);
+
+	for writer in writers {
+		match writer.flush() {
+			Ok(_) => (),
+			Err(_e) => return Err(anyhow!("Failed to write to output file")),
+		};
+	}
+
+	let inferred_types = column_types
+		.iter()
+		.enumerate()
+		.skip(declared_types)
+		.map(|(i, t)| InferredType {
+			// column syntax is 1 based, so add 1
+			column: format!("c{}", i + 1),
+			column_type: t.to_string(),
+		})
+		.collect();
+
+	let report = FilterReport {
+		total_lines,
+		valid_lines: total_lines - skipped_lines - comment_lines,
+		kept_lines: lines_kept,
+		rejected_lines,
+		skipped_lines,
+		comment_lines,
+		invalid_lines,
+		first_invalid_line: (invalid_lines > 0).then_some(first_invalid_line),
+		first_invalid_content: (invalid_lines > 0).then_some(invalid_line_content),
+		outputs: partitions
+			.iter()
+			.zip(partition_lines)
+			.map(|(partition, kept)| OutputReport {
+				file: partition.name.clone(),
+				expression: partition.expression.clone(),
+				kept_lines: kept,
+			})
+			.collect(),
+		inferred_types,
+		elapsed_seconds: start.elapsed().as_secs_f64(),
+		keep_comments,
+		comment_prefixes: options.comment_prefixes.clone(),
 	};
 
-	let mut report = String::new();
-	let valid_lines = total_lines - skipped_lines;
+	Ok(report)
+}
+
+/// evaluate `lines` with `evaluate`, split evenly between one thread per context.
+/// The results are in the same order as `lines`
+fn evaluate_in_parallel<T: Send>(
+	lines: &LineBuffer,
+	contexts: &mut [LineContext],
+	evaluate: &(impl Fn(usize, &[u8], &mut LineContext) -> T + Sync),
+) -> Vec<T> {
+	if contexts.len() == 1 {
+		return lines
+			.iter()
+			.map(|(n, l)| evaluate(n, l, &mut contexts[0]))
+			.collect();
+	}
 
-	if valid_lines > 0 {
-		report += &format!(
-			"Kept {:.2}% of {} valid lines ({} total lines)\n",
-			100.0 * lines_kept as f64 / valid_lines as f64,
-			valid_lines,
-			total_lines
-		);
+	let chunk_size = lines.len().div_ceil(contexts.len());
+
+	std::thread::scope(|scope| {
+		let threads: Vec<_> = (0..lines.len())
+			.step_by(chunk_size)
+			.zip(contexts.iter_mut())
+			.map(|(start, ctx)| {
+				let chunk = start..(start + chunk_size).min(lines.len());
+				scope.spawn(move || {
+					chunk
+						.map(|i| {
+							let (line_number, line) = lines.get(i);
+							evaluate(line_number, line, ctx)
+						})
+						.collect::<Vec<_>>()
+				})
+			})
+			.collect();
+
+		threads
+			.into_iter()
+			.flat_map(|t| t.join().unwrap())
+			.collect()
+	})
+}
+
+/// read the skipped lines and the header line of `reader` into `buffer`.
+/// Returns the column names of the header, or no names without header
+fn read_header(
+	buffer: &mut LineBuffer,
+	reader: &mut impl BufRead,
+	options: &FilterOptions,
+) -> Result<Vec<String>, anyhow::Error> {
+	for _ in 0..options.skip_lines + options.header as usize {
+		if read_line(buffer, reader)?.is_none() {
+			break;
+		}
+	}
+
+	if !options.header {
+		return Ok(Vec::new());
+	}
+
+	match buffer.iter().nth(options.skip_lines) {
+		Some((_, line)) => Ok(parse_header(line, options.delimiter, options.encoding)),
+		None => Err(anyhow!(
+			"Failed to read header line at line number {}",
+			options.skip_lines
+		)),
+	}
+}
+
+/// read lines of `reader` into `buffer`, until it holds `lines` data lines
+fn read_data_lines(
+	buffer: &mut LineBuffer,
+	reader: &mut impl BufRead,
+	options: &FilterOptions,
+	lines: usize,
+) -> Result<(), anyhow::Error> {
+	let mut data_lines = buffer
+		.iter()
+		.filter(|(_, l)| options.is_data_line(l))
+		.count();
+
+	while data_lines < lines {
+		match read_line(buffer, reader)? {
+			Some(l) if options.is_data_line(l) => data_lines += 1,
+			Some(_) => (),
+			None => break,
+		}
+	}
+
+	Ok(())
+}
+
+/// An expression compiled by `compile_expressions`, and the columns it uses
+struct CompiledExpression {
+	node: Node,
+	columns: Vec<ColumnVariable>,
+}
+
+impl CompiledExpression {
+	/// evaluate the expression for `context`, where the columns in `na_columns` have a missing value.
+	/// Returns `None` if the expression is unknown, because of a comparison with a missing value
+	fn evaluate(
+		&self,
+		context: &LineContext,
+		na_columns: &[usize],
+	) -> Result<Option<bool>, anyhow::Error> {
+		let uses_missing_value = self.columns.iter().any(|c| na_columns.contains(&c.index));
+
+		// missing values returned by functions are only found when evaluation fails
+		let result = match uses_missing_value {
+			true => eval_with_missing_values(&self.node, context),
+			false => match self.node.eval_boolean_with_context(context) {
+				Ok(passed) => Ok(Some(passed)),
+				Err(_) => eval_with_missing_values(&self.node, context),
+			},
+		};
+
+		result.map_err(|e| anyhow!("Evaluation failed: {e}"))
+	}
+}
+
+/// Expressions compiled for the column types of the input
+struct CompiledExpressions {
+	/// declared and inferred column types
+	column_types: Vec<ColumnType>,
+
+	/// number of declared column types, the following ones are inferred
+	declared_types: usize,
+
+	expressions: Vec<CompiledExpression>,
+
+	/// columns used by any expression, sorted by index.
+	/// All expressions share the columns set in the context
+	columns: Vec<ColumnVariable>,
+
+	/// line variables used by any expression
+	line_variables: LineVariables,
+}
+
+/// infer the column types from the data lines in `sample`, and compile `expressions` for them
+fn compile_expressions(
+	expressions: &[String],
+	sample: &LineBuffer,
+	header_names: &Vec<String>,
+	options: &FilterOptions,
+) -> Result<CompiledExpressions, anyhow::Error> {
+	let header = options.header.then_some(header_names);
+	let data = || {
+		sample
+			.iter()
+			.map(|(_, l)| l)
+			.filter(|l| options.is_data_line(l))
+	};
+
+	let declared_types = declared_types(
+		options,
+		expressions.iter().map(|e| e.as_str()),
+		header,
+		data(),
+	)?;
+
+	let column_types = if options.infer_lines > 0 {
+		let rows: Vec<Vec<String>> = data()
+			.take(options.infer_lines)
+			.map(|l| {
+				options
+					.delimiter
+					.split(l)
+					.map(|v| match options.encoding.decode(&v) {
+						// missing values have no type
+						v if options.is_na(&v) => String::new(),
+						v => v.into_owned(),
+					})
+					.collect()
+			})
+			.collect();
+
+		infer_column_types(&rows, &declared_types, &options.list_separator)
 	} else {
-		report += &format!(
-			"No lines kept. Check filter condition '{expression}', see tool tips, syntax and examples\n"
-		);
+		declared_types.clone()
+	};
+
+	let mut compiled = Vec::new();
+	let mut columns: Vec<ColumnVariable> = Vec::new();
+	let mut line_variables = LineVariables::default();
+
+	for expression in expressions {
+		let (node, used_columns, used_line_variables) =
+			compile_expression(expression, &column_types, header, options)?;
+
+		for column in &used_columns {
+			if !columns.iter().any(|c| c.name == column.name) {
+				columns.push(column.clone());
+			}
+		}
+
+		line_variables = line_variables.union(used_line_variables);
+		compiled.push(CompiledExpression {
+			node,
+			columns: used_columns,
+		});
 	}
 
-	if invalid_lines > 0 {
-		report += &format!(
-			"Skipped {invalid_lines} invalid line(s) starting at line {first_invalid_line}: '{invalid_line_content}'\n"
-		);
+	columns.sort_by_key(|c| c.index);
+
+	Ok(CompiledExpressions {
+		column_types,
+		declared_types: declared_types.len(),
+		expressions: compiled,
+		columns,
+		line_variables,
+	})
+}
+
+/// read the next line of `reader` into `buffer`.
+/// Returns `None` at the end of the input
+fn read_line<'a>(
+	buffer: &'a mut LineBuffer,
+	reader: &mut impl BufRead,
+) -> Result<Option<&'a [u8]>, anyhow::Error> {
+	let line_number = buffer.lines_read();
+
+	match buffer.read_line(reader) {
+		Ok(line) => Ok(line),
+		Err(_) => Err(anyhow!("Failed to read file at line number {line_number}")),
 	}
+}
 
-	if skipped_lines > 0 {
-		report += &format!("Skipped {skipped_lines} comment (starting with #) or blank line(s)\n");
+/// find the index of the first expression in `expressions` passing for `context`.
+/// Expressions unknown because of a missing value in `na_columns` are false or an error,
+/// depending on `na_comparisons`
+fn first_passed(
+	expressions: &[CompiledExpression],
+	context: &LineContext,
+	na_columns: &[usize],
+	na_comparisons: NaComparisons,
+) -> Result<Option<usize>, anyhow::Error> {
+	for (i, expression) in expressions.iter().enumerate() {
+		match (expression.evaluate(context, na_columns)?, na_comparisons) {
+			(Some(true), _) => return Ok(Some(i)),
+			(Some(false), _) | (None, NaComparisons::False) => (),
+			(None, NaComparisons::Invalid) => {
+				return Err(anyhow!("Comparison with a missing value"))
+			}
+		}
 	}
 
-	Ok(report)
+	Ok(None)
 }
 
-/// compile and test run the expression to check for any errors
+/// A column used in the expression, and the variable name it is available as
+#[derive(Clone)]
+struct ColumnVariable {
+	name: String,
+	index: usize,
+}
+
+/// Variable holding the line number, starting at 1 like in awk
+const LINE_NUMBER_VARIABLE: &str = "NR";
+
+/// Variable holding the number of fields of the line
+const FIELD_COUNT_VARIABLE: &str = "NF";
+
+/// Variable holding the whole line, without line ending
+const LINE_VARIABLE: &str = "line";
+
+/// The variables describing the whole line, which are used in the expression.
+/// Header columns of the same name take precedence
+#[derive(Clone, Copy, Debug, Default)]
+struct LineVariables {
+	line_number: bool,
+	field_count: bool,
+	line: bool,
+}
+
+impl LineVariables {
+	/// find the line variables used in `node`, which are not header columns
+	fn find(node: &Node, header: Option<&Vec<String>>) -> LineVariables {
+		let is_used = |variable: &str| {
+			node.iter_variable_identifiers().any(|i| i == variable)
+				&& !header.is_some_and(|names| names.iter().any(|n| n == variable))
+		};
+
+		LineVariables {
+			line_number: is_used(LINE_NUMBER_VARIABLE),
+			field_count: is_used(FIELD_COUNT_VARIABLE),
+			line: is_used(LINE_VARIABLE),
+		}
+	}
+
+	/// the line variables used by either `self` or `other`
+	fn union(self, other: LineVariables) -> LineVariables {
+		LineVariables {
+			line_number: self.line_number || other.line_number,
+			field_count: self.field_count || other.field_count,
+			line: self.line || other.line,
+		}
+	}
+}
+
+/// compile and test run the expression to check for any errors.
+/// Returns the compiled expression, and the columns and line variables it uses
 fn compile_expression(
-	columns: &[usize],
+	expression: &str,
 	column_types: &[ColumnType],
-	expression: &String,
-) -> Result<Node, anyhow::Error> {
-	let precompiled_exp = match build_operator_tree(expression) {
+	header: Option<&Vec<String>>,
+	options: &FilterOptions,
+) -> Result<(Node, Vec<ColumnVariable>, LineVariables), anyhow::Error> {
+	let expression = resolve_columns(expression, header)?;
+
+	let precompiled_exp = match build_operator_tree(&expression) {
 		Ok(n) => n,
 		Err(e) => {
 			return Err(anyhow!(
@@ -233,25 +1124,58 @@ fn compile_expression(
 		}
 	};
 
-	let mut mock_values = Vec::new();
+	let mut columns = get_used_columns(&precompiled_exp, &expression)?;
+
+	if let Some(names) = header {
+		columns.append(&mut get_named_columns(&precompiled_exp, names)?);
+	}
 
-	for t in column_types {
-		match t {
-			ColumnType::Bool => mock_values.push("true"),
-			ColumnType::Float => mock_values.push("0.1"),
-			ColumnType::Int => mock_values.push("0"),
-			ColumnType::Str => mock_values.push("string"),
-			ColumnType::None => mock_values.push(""),
-			ColumnType::List => mock_values.push("1,2,3"),
+	// without declared types, columns missing from the sample are empty, like in short lines
+	for column in &columns {
+		if column.index >= column_types.len() && !options.column_types.is_empty() {
+			return Err(point_at(
+				&expression,
+				&column.name,
+				format!(
+					"Column '{}' is used, but only {} column type(s) are known. Declare its type with --types",
+					column.name,
+					column_types.len()
+				),
+			));
 		}
 	}
 
+	columns.sort_by_key(|c| c.index);
+
+	let line_variables = LineVariables::find(&precompiled_exp, header);
+
+	let mock_values: Vec<String> = column_types
+		.iter()
+		.map(|t| mock_value(t, &options.list_separator))
+		.collect();
+
+	// the mock line is always tab separated, so empty values are kept
+	let mock_options = FilterOptions {
+		delimiter: Delimiter::Tab,
+		encoding: Encoding::Utf8,
+		..options.clone()
+	};
+
 	let mock_line = mock_values.join("\t");
-	let mut context = create_function_context();
-	mutate_context_for_line(&mock_line, column_types, columns, &mut context).unwrap();
+	let mut context = LineContext::new();
+	mutate_context_for_line(
+		mock_line.as_bytes(),
+		0,
+		column_types,
+		&columns,
+		line_variables,
+		&mock_options,
+		&mut context,
+	)
+	.map_err(|e| anyhow!("Invalid column type or format, {e}"))?;
 
 	match precompiled_exp.eval_boolean_with_context(&context) {
-		Ok(_) => Ok(precompiled_exp),
+		Ok(_) => Ok((precompiled_exp, columns, line_variables)),
 		Err(e) => {
 			Err(anyhow!(
 				"Expression test failed for expression: '{expression}'. Please check the syntax and column types. \n Detailed Error: \n {e}"
@@ -260,64 +1184,544 @@ fn compile_expression(
 	}
 }
 
-/// find columns potentially used in expression
-fn get_used_columns(expression: &str) -> Vec<usize> {
+/// replace quoted column names and column ranges in `expression` with positional columns
+fn resolve_columns(
+	expression: &str,
+	header: Option<&Vec<String>>,
+) -> Result<String, anyhow::Error> {
+	let expression = match header {
+		Some(names) => resolve_quoted_names(expression, names)?,
+		None => expression.to_owned(),
+	};
+
+	expand_column_ranges(&expression)
+}
+
+/// the declared column types.
+/// A last repeated type like `float*` is expanded to every column of the header,
+/// the `sample` lines and the columns used in `expressions`
+fn declared_types<'a, 'b>(
+	options: &FilterOptions,
+	expressions: impl Iterator<Item = &'a str>,
+	header: Option<&Vec<String>>,
+	sample: impl Iterator<Item = &'b [u8]>,
+) -> Result<Vec<ColumnType>, anyhow::Error> {
+	let types = &options.column_types;
+	let is_repeated = |t: &ColumnType| matches!(t, ColumnType::Repeated(_));
+
+	if types.iter().rev().skip(1).any(is_repeated) {
+		return Err(anyhow!(
+			"Only the last column type can be repeated, like 'str,int,float*'"
+		));
+	}
+
+	let (repeated, declared) = match types.split_last() {
+		Some((ColumnType::Repeated(t), declared)) => (t, declared),
+		_ => return Ok(types.clone()),
+	};
+
+	// expressions which don't compile are reported later
+	let highest_column = |expression: &str| {
+		let expression = resolve_columns(expression, header).ok()?;
+		let node = build_operator_tree(&expression).ok()?;
+		node.iter_variable_identifiers()
+			.filter_map(column_number)
+			.max()
+	};
+
+	let columns = expressions
+		.filter_map(highest_column)
+		.chain(header.map(|names| names.len()))
+		.chain(sample.map(|line| options.delimiter.split(line).count()))
+		.fold(declared.len(), usize::max);
+
+	let mut types = declared.to_vec();
+	types.resize(columns, (**repeated).clone());
+
+	Ok(types)
+}
+
+/// find the positional columns (`c1`, `c2`, ...) used as variables in `node`
+fn get_used_columns(node: &Node, expression: &str) -> Result<Vec<ColumnVariable>, anyhow::Error> {
+	let mut columns: Vec<ColumnVariable> = Vec::new();
+
+	for identifier in node.iter_variable_identifiers() {
+		let number = match column_number(identifier) {
+			Some(n) => n,
+			None => continue,
+		};
+
+		if number == 0 {
+			return Err(point_at(
+				expression,
+				identifier,
+				"Column numbers start at 1, 'c0' does not exist".to_owned(),
+			));
+		}
+
+		if !columns.iter().any(|c| c.name == identifier) {
+			columns.push(ColumnVariable {
+				name: identifier.to_owned(),
+				// column syntax is 1 based, so subtract 1
+				index: number - 1,
+			});
+		}
+	}
+
+	Ok(columns)
+}
+
+/// get the number of a positional column identifier like `c12`
+fn column_number(identifier: &str) -> Option<usize> {
+	let digits = identifier.strip_prefix('c')?;
+
+	if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+		return None;
+	}
+
+	digits.parse::<usize>().ok()
+}
+
+/// whether `c` can be part of an identifier in an expression
+fn is_identifier_char(c: char) -> bool {
+	c.is_alphanumeric() || c == '_' || c == '.' || c == ':'
+}
+
+/// find the position of `identifier` in `expression`, ignoring string literals
+fn find_identifier(expression: &str, identifier: &str) -> Option<usize> {
+	let mut in_string = false;
+	let mut escaped = false;
+	let mut previous = ' ';
+
+	for (position, c) in expression.char_indices() {
+		if in_string {
+			match c {
+				_ if escaped => escaped = false,
+				'\\' => escaped = true,
+				'"' => in_string = false,
+				_ => (),
+			}
+		} else if c == '"' {
+			in_string = true;
+		} else if !is_identifier_char(previous) && expression[position..].starts_with(identifier) {
+			let end = position + identifier.len();
+
+			if !expression[end..].starts_with(is_identifier_char) {
+				return Some(position);
+			}
+		}
+
+		previous = c;
+	}
+
+	None
+}
+
+/// error with `message`, showing where `identifier` is used in `expression`
+fn point_at(expression: &str, identifier: &str, message: String) -> anyhow::Error {
+	match find_identifier(expression, identifier) {
+		Some(position) => anyhow!(
+			"{message}\n {expression}\n {}^",
+			" ".repeat(expression[..position].chars().count())
+		),
+		None => anyhow!("{message}"),
+	}
+}
+
+/// split a header line into column names.
+/// Leading comment characters are not part of the first name
+fn parse_header(line: &[u8], delimiter: Delimiter, encoding: Encoding) -> Vec<String> {
+	let start = line.iter().position(|b| *b != b'#').unwrap_or(line.len());
+
+	delimiter
+		.split(&line[start..])
+		.map(|name| encoding.decode(&name).trim().to_owned())
+		.collect()
+}
+
+/// error for a column name not found in the header
+fn unknown_column_error(name: &str, header: &[String]) -> anyhow::Error {
+	anyhow!(
+		"Unknown column '{name}'. Available columns are: {}",
+		header.join(", ")
+	)
+}
+
+/// replace backtick quoted column names in `expression` with their positional identifier
+fn resolve_quoted_names(expression: &str, header: &[String]) -> Result<String, anyhow::Error> {
+	let mut resolved = String::new();
+	let mut chars = expression.chars();
+	let mut in_string = false;
+
+	while let Some(c) = chars.next() {
+		match c {
+			'"' => {
+				in_string = !in_string;
+				resolved.push(c);
+			}
+			'\\' if in_string => {
+				resolved.push(c);
+				if let Some(escaped) = chars.next() {
+					resolved.push(escaped);
+				}
+			}
+			'`' if !in_string => {
+				let name: String = chars.by_ref().take_while(|c| *c != '`').collect();
+
+				match header.iter().position(|h| *h == name) {
+					// column syntax is 1 based, so add 1
+					Some(i) => resolved += &format!("c{}", i + 1),
+					None => return Err(unknown_column_error(&name, header)),
+				}
+			}
+			c => resolved.push(c),
+		}
+	}
+
+	Ok(resolved)
+}
+
+/// A column range like `c4..c9` in an expression
+struct ColumnRange {
+	/// position of the range in the expression
+	position: Range<usize>,
+	first: usize,
+	last: usize,
+	/// position of the list function argument containing the range
+	argument: Range<usize>,
+	/// number of list function calls the range is nested in
+	depth: usize,
+}
+
+/// Functions taking a list, in whose arguments column ranges are expanded
+const LIST_FUNCTIONS: [&str; 15] = [
+	"any",
+	"all",
+	"count",
+	"any_cmp",
+	"all_cmp",
+	"count_cmp",
+	"sum",
+	"min",
+	"max",
+	"len",
+	"first",
+	"last",
+	"index",
+	"in",
+	"coalesce",
+];
+
+/// get the first and last column number of a column range identifier like `c4..c9`
+fn column_range(identifier: &str) -> Option<(usize, usize)> {
+	let (first, last) = identifier.split_once("..")?;
+
+	Some((column_number(first)?, column_number(last)?))
+}
+
+/// find the column ranges in `expression`, ignoring string literals
+fn find_column_ranges(expression: &str) -> Result<Vec<ColumnRange>, anyhow::Error> {
+	let mut ranges: Vec<ColumnRange> = Vec::new();
+	// start of every open argument, whether it belongs to a list function,
+	// and the ranges in it
+	let mut arguments: Vec<(usize, bool, Vec<usize>)> = Vec::new();
+	let mut in_string = false;
+	let mut escaped = false;
+	let mut previous = ' ';
+	let mut previous_token = ' ';
+	let mut previous_identifier = "";
+
+	for (position, c) in expression.char_indices() {
+		if in_string {
+			match c {
+				_ if escaped => escaped = false,
+				'\\' => escaped = true,
+				'"' => in_string = false,
+				_ => (),
+			}
+		} else {
+			match c {
+				'"' => in_string = true,
+				'(' => {
+					let is_list_function = is_identifier_char(previous_token)
+						&& LIST_FUNCTIONS.contains(&previous_identifier);
+					arguments.push((position + 1, is_list_function, Vec::new()))
+				}
+				',' | ')' => {
+					if let Some((start, _, in_argument)) = arguments.last_mut() {
+						for i in in_argument.drain(..) {
+							ranges[i].argument = *start..position;
+						}
+
+						*start = position + 1;
+					}
+
+					if c == ')' {
+						arguments.pop();
+					}
+				}
+				_ if is_identifier_char(c) && !is_identifier_char(previous) => {
+					let end = match expression[position..].find(|c| !is_identifier_char(c)) {
+						Some(length) => position + length,
+						None => expression.len(),
+					};
+					let identifier = &expression[position..end];
+					previous_identifier = identifier;
+
+					if let Some((first, last)) = column_range(identifier) {
+						if first > last {
+							return Err(point_at(
+								expression,
+								identifier,
+								format!("Column range '{identifier}' has to start with the lower column"),
+							));
+						}
+
+						let depth = arguments.iter().filter(|(_, list, _)| *list).count();
+
+						// the innermost list function, so `any(abs(c1..c3) > 5)` compares every `abs`
+						match arguments.iter_mut().rev().find(|(_, list, _)| *list) {
+							Some((start, _, in_argument)) => {
+								in_argument.push(ranges.len());
+								ranges.push(ColumnRange {
+									position: position..end,
+									first,
+									last,
+									// until the end of the argument is found
+									argument: *start..expression.len(),
+									depth,
+								});
+							}
+							_ => {
+								return Err(point_at(
+									expression,
+									identifier,
+									format!("Column range '{identifier}' can only be used in the argument of a list function, like any({identifier} > 0)"),
+								))
+							}
+						}
+					}
+				}
+				_ => (),
+			}
+		}
+
+		previous = c;
+
+		if !c.is_whitespace() {
+			previous_token = c;
+		}
+	}
+
+	Ok(ranges)
+}
+
+/// expand the column ranges in `expression` to a list with one value per column.
+/// The list function argument containing a range is repeated for every column,
+/// so `any(abs(c1..c3) > 5)` becomes `any((abs(c1) > 5, abs(c2) > 5, abs(c3) > 5))`
+fn expand_column_ranges(expression: &str) -> Result<String, anyhow::Error> {
+	let mut expression = expression.to_owned();
+
+	loop {
+		let ranges = find_column_ranges(&expression)?;
+
+		// innermost list functions first, so their ranges are not repeated by the outer call
+		let argument = match ranges.iter().max_by_key(|r| r.depth) {
+			Some(range) => range.argument.clone(),
+			None => return Ok(expression),
+		};
+
+		let in_argument: Vec<&ColumnRange> =
+			ranges.iter().filter(|r| r.argument == argument).collect();
+		let length = in_argument[0].last - in_argument[0].first + 1;
+
+		if in_argument.iter().any(|r| r.last - r.first + 1 != length) {
+			return Err(anyhow!(
+				"Column ranges in the same argument need the same length: '{}'",
+				expression[argument].trim()
+			));
+		}
+
+		let elements: Vec<String> = (0..length)
+			.map(|i| {
+				let mut element = String::new();
+				let mut position = argument.start;
+
+				for range in &in_argument {
+					element += &expression[position..range.position.start];
+					element += &format!("c{}", range.first + i);
+					position = range.position.end;
+				}
+
+				element += &expression[position..argument.end];
+				element.trim().to_owned()
+			})
+			.collect();
+
+		let expanded = match elements.len() {
+			1 => elements[0].clone(),
+			_ => format!("({})", elements.join(", ")),
+		};
+
+		expression.replace_range(argument, &expanded);
+	}
+}
+
+/// find columns referenced by their header name in `node`
+fn get_named_columns(node: &Node, header: &[String]) -> Result<Vec<ColumnVariable>, anyhow::Error> {
 	let mut columns = Vec::new();
-	let r = Regex::new(r"c(?P<column>[0-9]+)").unwrap();
 
-	for captures in r.captures_iter(expression) {
-		let m = captures.name("column").unwrap();
-		let i = m.as_str().parse::<usize>().unwrap();
-		// column syntax is 1 based, so subtract 1
-		columns.push(i - 1);
+	for identifier in node.iter_variable_identifiers() {
+		if column_number(identifier).is_some() {
+			continue;
+		}
+
+		let is_line_variable =
+			[LINE_NUMBER_VARIABLE, FIELD_COUNT_VARIABLE, LINE_VARIABLE].contains(&identifier);
+
+		match header.iter().position(|h| h == identifier) {
+			Some(index) => columns.push(ColumnVariable {
+				name: identifier.to_owned(),
+				index,
+			}),
+			None if is_line_variable => (),
+			None => return Err(unknown_column_error(identifier, header)),
+		}
 	}
 
-	columns
+	Ok(columns)
+}
+
+/// a valid value of type `t`, to test run expressions with
+fn mock_value(t: &ColumnType, list_separator: &str) -> String {
+	match t {
+		ColumnType::Bool => "true".to_owned(),
+		ColumnType::Float => "0.1".to_owned(),
+		ColumnType::Int => "0".to_owned(),
+		ColumnType::Str => "string".to_owned(),
+		ColumnType::None => String::new(),
+		ColumnType::List(t) => vec![mock_value(t, list_separator); 3].join(list_separator),
+		ColumnType::Date(format) => format_date(0, format.as_deref().unwrap_or(DATE_FORMAT)),
+		ColumnType::DateTime(format) => {
+			format_date(0, format.as_deref().unwrap_or("%Y-%m-%dT%H:%M:%S"))
+		}
+		ColumnType::Repeated(t) => mock_value(t, list_separator),
+	}
 }
 
 /// Mutates `context` for a line from the file, containing needed variables.
-/// Returns unspecific error for invalid lines
+/// `columns` have to be sorted by index, so the line is only split up to the last used column.
+/// Returns the indices of used columns with a missing value, or unspecific error for invalid lines
 fn mutate_context_for_line(
-	line: &str,
+	line: &[u8],
+	line_number: usize,
 	column_types: &[ColumnType],
-	columns: &[usize],
-	context: &mut HashMapContext,
-) -> Result<(), anyhow::Error> {
-	let split_line = line.split('\t').collect::<Vec<&str>>();
+	columns: &[ColumnVariable],
+	line_variables: LineVariables,
+	options: &FilterOptions,
+	context: &mut LineContext,
+) -> Result<Vec<usize>, anyhow::Error> {
+	let mut na_columns = Vec::new();
+
+	if line_variables.line_number {
+		// line numbers are 0 based internally
+		let number = Value::Int(line_number as i64 + 1);
+		context.set_value(LINE_NUMBER_VARIABLE, number);
+	}
+
+	if line_variables.field_count {
+		let count = Value::Int(options.delimiter.split(line).count() as i64);
+		context.set_value(FIELD_COUNT_VARIABLE, count);
+	}
+
+	if line_variables.line {
+		let line = Value::String(options.encoding.decode(line).into_owned());
+		context.set_value(LINE_VARIABLE, line);
+	}
+
+	let mut values = options.delimiter.split(line);
+	let mut value = values.next();
+	let mut position = 0;
 
 	for column in columns {
-		let t = match column_types.get(*column) {
+		while position < column.index {
+			value = values.next();
+			position += 1;
+		}
+
+		let t = match column_types.get(column.index) {
 			Some(t) => t,
 			None => &ColumnType::None,
 		};
 
-		// column syntax is 1 based, so add 1
-		let mut set = |v: Value| context.set_value(format!("c{}", column + 1), v);
+		let mut set = |v: Value| context.set_value(&column.name, v);
 
-		let str_value = match split_line.get(*column) {
-			Some(&s) => s,
+		let str_value = match &value {
+			Some(s) => options.encoding.decode(s),
 			None => {
 				// if value can't be found, set it to empty
-				set(Value::Empty)?;
+				set(Value::Empty);
 				continue;
 			}
 		};
 
-		match t {
-			ColumnType::Bool => set(Value::Boolean(str_value.parse::<bool>()?))?,
-			ColumnType::Float => set(Value::Float(str_value.parse::<f64>()?))?,
-			ColumnType::Int => set(Value::Int(str_value.parse::<i64>()?))?,
-			ColumnType::Str => set(Value::String(str_value.to_string()))?,
-			ColumnType::None => set(Value::Empty)?,
-			ColumnType::List => set(Value::Tuple(
-				str_value
-					.split(',')
-					.into_iter()
-					.map(|s| Value::String(s.to_owned()))
-					.collect(),
-			))?,
+		if options.is_na(&str_value) {
+			set(Value::Empty);
+			na_columns.push(column.index);
+			continue;
+		}
+
+		match parse_value(&str_value, t, &options.list_separator) {
+			Ok(v) => set(v),
+			Err(e) => return Err(anyhow!("Column {}: {e}", column.name)),
 		};
 	}
 
-	Ok(())
+	Ok(na_columns)
+}
+
+/// parse `value` from a column of type `t`.
+/// List elements are separated by `list_separator`
+fn parse_value(value: &str, t: &ColumnType, list_separator: &str) -> Result<Value, anyhow::Error> {
+	let invalid = || anyhow!("'{value}' is not a valid {t}");
+
+	match t {
+		ColumnType::Bool => Ok(Value::Boolean(
+			value.parse::<bool>().map_err(|_| invalid())?,
+		)),
+		ColumnType::Float => Ok(Value::Float(value.parse::<f64>().map_err(|_| invalid())?)),
+		ColumnType::Int => Ok(Value::Int(value.parse::<i64>().map_err(|_| invalid())?)),
+		ColumnType::Str => Ok(Value::String(value.to_string())),
+		ColumnType::None => Ok(Value::Empty),
+		ColumnType::Date(format) => match parse_date(value, format.as_deref()) {
+			Some(seconds) => Ok(Value::Int(seconds)),
+			None => Err(invalid()),
+		},
+		ColumnType::DateTime(format) => match parse_datetime(value, format.as_deref()) {
+			Some(seconds) => Ok(Value::Int(seconds)),
+			None => Err(invalid()),
+		},
+		ColumnType::Repeated(t) => parse_value(value, t, list_separator),
+		ColumnType::List(t) => {
+			// lists like BED blockSizes may end with a separator
+			let value = value.strip_suffix(list_separator).unwrap_or(value);
+
+			if value.is_empty() {
+				return Ok(Value::Tuple(Vec::new()));
+			}
+
+			let elements = value
+				.split(list_separator)
+				.map(|element| parse_value(element, t, list_separator))
+				.collect::<Result<Vec<Value>, anyhow::Error>>();
+
+			match elements {
+				Ok(elements) => Ok(Value::Tuple(elements)),
+				Err(e) => Err(anyhow!(
+					"'{value}' is not a valid {}: {e}",
+					ColumnType::List(t.clone())
+				)),
+			}
+		}
+	}
 }
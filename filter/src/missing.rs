@@ -0,0 +1,98 @@
+use evalexpr::{Context, EvalexprError, EvalexprResult, Node, Operator, Value};
+
+/// whether `node` reads a variable with a missing value
+fn reads_missing_value(node: &Node, context: &impl Context) -> bool {
+	node.iter_variable_identifiers()
+		.any(|identifier| matches!(context.get_value(identifier), Some(Value::Empty)))
+}
+
+/// evaluate `node` as boolean in three-valued logic, like SQL.
+/// Comparisons with a missing value are unknown, which is `None`.
+/// `||` and `&&` are only unknown if the known side doesn't decide them,
+/// so `is_na(c1) || c1 > 5` is true for a missing `c1`
+pub fn eval_with_missing_values(
+	node: &Node,
+	context: &impl Context,
+) -> EvalexprResult<Option<bool>> {
+	let children = node.children();
+
+	match node.operator() {
+		Operator::RootNode if children.len() == 1 => {
+			eval_with_missing_values(&children[0], context)
+		}
+		Operator::And => match eval_with_missing_values(&children[0], context)? {
+			Some(false) => Ok(Some(false)),
+			a => match eval_with_missing_values(&children[1], context)? {
+				Some(false) => Ok(Some(false)),
+				b => Ok(a.and(b)),
+			},
+		},
+		Operator::Or => match eval_with_missing_values(&children[0], context)? {
+			Some(true) => Ok(Some(true)),
+			a => match eval_with_missing_values(&children[1], context)? {
+				Some(true) => Ok(Some(true)),
+				b => Ok(a.and(b)),
+			},
+		},
+		Operator::Not => Ok(eval_with_missing_values(&children[0], context)?.map(|b| !b)),
+		Operator::Eq
+		| Operator::Neq
+		| Operator::Gt
+		| Operator::Lt
+		| Operator::Geq
+		| Operator::Leq => {
+			for operand in children {
+				match operand.eval_with_context(context) {
+					Ok(Value::Empty) => return Ok(None),
+					Ok(_) => (),
+					Err(_) if reads_missing_value(operand, context) => return Ok(None),
+					Err(e) => return Err(e),
+				}
+			}
+
+			node.eval_boolean_with_context(context).map(Some)
+		}
+		_ => match node.eval_with_context(context) {
+			Ok(Value::Boolean(b)) => Ok(Some(b)),
+			Ok(Value::Empty) => Ok(None),
+			Ok(v) => Err(EvalexprError::expected_boolean(v)),
+			Err(_) if reads_missing_value(node, context) => Ok(None),
+			Err(e) => Err(e),
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::context::LineContext;
+	use evalexpr::build_operator_tree;
+
+	fn eval(expression: &str, context: &LineContext) -> EvalexprResult<Option<bool>> {
+		eval_with_missing_values(&build_operator_tree(expression).unwrap(), context)
+	}
+
+	#[test]
+	fn missing_values_are_unknown() {
+		let mut ctx = LineContext::new();
+		ctx.set_value("c1", Value::String("b".to_owned()));
+		ctx.set_value("c2", Value::Empty);
+		ctx.set_value("c3", Value::Int(5));
+
+		assert_eq!(eval("c2 > 10", &ctx), Ok(None));
+		assert_eq!(eval("c2 != 5", &ctx), Ok(None));
+		assert_eq!(eval("!(c2 == 5)", &ctx), Ok(None));
+		assert_eq!(eval("c2 + 1 > 10", &ctx), Ok(None));
+		assert_eq!(eval("c3 == 5 && c2 > 10", &ctx), Ok(None));
+
+		// the known side decides
+		assert_eq!(eval("is_na(c2) || c2 > 10", &ctx), Ok(Some(true)));
+		assert_eq!(eval(r#"c1 == "b" || c2 > 10"#, &ctx), Ok(Some(true)));
+		assert_eq!(eval("c2 > 10 && c3 > 5", &ctx), Ok(Some(false)));
+		assert_eq!(eval("coalesce(c2, 0) < c3", &ctx), Ok(Some(true)));
+
+		// errors without missing values are kept
+		assert!(eval("c3 / 0 > 1 || c2 > 10", &ctx).is_err());
+		assert!(eval("c3 + 1", &ctx).is_err());
+	}
+}
@@ -0,0 +1,840 @@
+use anyhow::anyhow;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// Python keywords which are not part of the expression subset Galaxy permits
+const UNSUPPORTED_KEYWORDS: [&str; 26] = [
+	"lambda", "if", "else", "elif", "for", "while", "import", "from", "def", "class", "return",
+	"yield", "del", "with", "as", "global", "nonlocal", "assert", "pass", "break", "continue",
+	"raise", "try", "except", "finally", "await",
+];
+
+/// Python functions and the functions they are translated to
+const FUNCTIONS: [(&str, &str); 17] = [
+	("abs", "abs"),
+	("len", "len"),
+	("min", "min"),
+	("max", "max"),
+	("pow", "pow"),
+	("round", "round"),
+	("log", "log"),
+	("exp", "exp"),
+	("sqrt", "sqrt"),
+	("ceil", "ceil"),
+	("floor", "floor"),
+	("int", "int"),
+	("float", "float"),
+	("str", "str"),
+	("sum", "sum"),
+	("any", "any"),
+	("all", "all"),
+];
+
+/// operators, longest first so that e.g. `**` is not read as two `*`
+const OPERATORS: [&str; 27] = [
+	"**", "//", "==", "!=", "<=", ">=", "<<", ">>", "+", "-", "*", "/", "%", "<", ">", "(", ")",
+	"[", "]", ",", ".", ":", "&", "|", "^", "~", "=",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+	Name(String),
+	Int(i64),
+	Float(f64),
+	Str(String),
+	Op(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+	Name(String),
+	Int(i64),
+	Float(f64),
+	Str(String),
+	Bool(bool),
+	None,
+	List(Vec<Expr>),
+	Unary(&'static str, Box<Expr>),
+	Binary(&'static str, Box<Expr>, Box<Expr>),
+	And(Box<Expr>, Box<Expr>),
+	Or(Box<Expr>, Box<Expr>),
+	Not(Box<Expr>),
+	Compare(Box<Expr>, Vec<(&'static str, Expr)>),
+	Call(String, Vec<Expr>),
+	Method(Box<Expr>, String, Vec<Expr>),
+	Index(Box<Expr>, Box<Expr>),
+	Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+}
+
+/// translate a python expression, as accepted by Galaxy's Filter1 tool, into an evalexpr expression
+pub fn translate_python(expression: &str) -> Result<String, anyhow::Error> {
+	let tokens = tokenize(expression)?;
+	let mut parser = Parser { tokens, pos: 0 };
+
+	let expr = parser.parse_or()?;
+
+	if let Some((position, token)) = parser.tokens.get(parser.pos) {
+		return Err(anyhow!(
+			"Unexpected {} at position {position} in python expression '{expression}'",
+			describe(token)
+		));
+	}
+
+	emit(&expr)
+}
+
+/// human readable description of `token` for error messages
+fn describe(token: &Token) -> String {
+	match token {
+		Token::Name(n) => format!("name '{n}'"),
+		Token::Int(i) => format!("number '{i}'"),
+		Token::Float(f) => format!("number '{f}'"),
+		Token::Str(s) => format!("string '{s}'"),
+		Token::Op(o) => format!("'{o}'"),
+	}
+}
+
+/// split `expression` into tokens, paired with their position in the expression
+fn tokenize(expression: &str) -> Result<Vec<(usize, Token)>, anyhow::Error> {
+	let mut tokens = Vec::new();
+	let mut chars = expression.char_indices().peekable();
+
+	while let Some(&(position, c)) = chars.peek() {
+		if c.is_whitespace() {
+			chars.next();
+		} else if c == '\'' || c == '"' {
+			chars.next();
+			tokens.push((position, Token::Str(read_string(&mut chars, c, position)?)));
+		} else if c.is_ascii_digit()
+			|| (c == '.' && expression[position + 1..].starts_with(|c: char| c.is_ascii_digit()))
+		{
+			tokens.push((position, read_number(&mut chars, expression, position)?));
+		} else if c.is_alphabetic() || c == '_' {
+			let mut name = String::new();
+
+			while let Some(&(_, c)) = chars.peek() {
+				if !(c.is_alphanumeric() || c == '_') {
+					break;
+				}
+				name.push(c);
+				chars.next();
+			}
+
+			tokens.push((position, Token::Name(name)));
+		} else {
+			let rest = &expression[position..];
+
+			match OPERATORS.iter().find(|o| rest.starts_with(*o)) {
+				// assignments are not part of the permitted subset
+				Some(&"=") | None => {
+					return Err(anyhow!(
+						"Unsupported character '{c}' at position {position} in python expression '{expression}'"
+					))
+				}
+				Some(o) => {
+					for _ in 0..o.len() {
+						chars.next();
+					}
+					tokens.push((position, Token::Op(o)));
+				}
+			}
+		}
+	}
+
+	Ok(tokens)
+}
+
+/// read a string literal closed by `quote`, resolving escape sequences
+fn read_string(
+	chars: &mut Peekable<CharIndices>,
+	quote: char,
+	start: usize,
+) -> Result<String, anyhow::Error> {
+	let mut string = String::new();
+
+	while let Some((_, c)) = chars.next() {
+		match c {
+			c if c == quote => return Ok(string),
+			'\\' => match chars.next() {
+				Some((_, 'n')) => string.push('\n'),
+				Some((_, 't')) => string.push('\t'),
+				Some((_, 'r')) => string.push('\r'),
+				Some((_, c)) => string.push(c),
+				None => break,
+			},
+			c => string.push(c),
+		}
+	}
+
+	Err(anyhow!("Unterminated string starting at position {start}"))
+}
+
+/// read an int or float literal
+fn read_number(
+	chars: &mut Peekable<CharIndices>,
+	expression: &str,
+	start: usize,
+) -> Result<Token, anyhow::Error> {
+	let mut end = start;
+	let mut previous = ' ';
+
+	while let Some(&(position, c)) = chars.peek() {
+		let exponent_sign = (c == '-' || c == '+') && (previous == 'e' || previous == 'E');
+
+		if !(c.is_ascii_alphanumeric() || c == '.' || c == '_' || exponent_sign) {
+			break;
+		}
+
+		previous = c;
+		end = position + c.len_utf8();
+		chars.next();
+	}
+
+	let literal = expression[start..end].replace('_', "");
+
+	if let Ok(i) = literal.parse::<i64>() {
+		Ok(Token::Int(i))
+	} else if let Ok(f) = literal.parse::<f64>() {
+		Ok(Token::Float(f))
+	} else {
+		Err(anyhow!("Invalid number '{literal}' at position {start}"))
+	}
+}
+
+/// recursive descent parser following python's operator precedence
+struct Parser {
+	tokens: Vec<(usize, Token)>,
+	pos: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.pos).map(|(_, t)| t)
+	}
+
+	fn peek_op(&self, op: &str) -> bool {
+		matches!(self.peek(), Some(Token::Op(o)) if *o == op)
+	}
+
+	fn peek_name(&self, name: &str) -> bool {
+		matches!(self.peek(), Some(Token::Name(n)) if n == name)
+	}
+
+	fn next(&mut self) -> Result<Token, anyhow::Error> {
+		match self.tokens.get(self.pos) {
+			Some((_, t)) => {
+				self.pos += 1;
+				Ok(t.clone())
+			}
+			None => Err(anyhow!("Unexpected end of python expression")),
+		}
+	}
+
+	fn expect_op(&mut self, op: &str) -> Result<(), anyhow::Error> {
+		if self.peek_op(op) {
+			self.pos += 1;
+			return Ok(());
+		}
+
+		match self.tokens.get(self.pos) {
+			Some((position, t)) => Err(anyhow!(
+				"Expected '{op}' but found {} at position {position}",
+				describe(t)
+			)),
+			None => Err(anyhow!("Expected '{op}' but the expression ended")),
+		}
+	}
+
+	/// find the first operator of `ops` at the current position
+	fn match_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+		let op = ops.iter().find(|o| self.peek_op(o))?;
+		self.pos += 1;
+		Some(op)
+	}
+
+	fn parse_or(&mut self) -> Result<Expr, anyhow::Error> {
+		let mut left = self.parse_and()?;
+
+		while self.peek_name("or") {
+			self.pos += 1;
+			left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+		}
+
+		Ok(left)
+	}
+
+	fn parse_and(&mut self) -> Result<Expr, anyhow::Error> {
+		let mut left = self.parse_not()?;
+
+		while self.peek_name("and") {
+			self.pos += 1;
+			left = Expr::And(Box::new(left), Box::new(self.parse_not()?));
+		}
+
+		Ok(left)
+	}
+
+	fn parse_not(&mut self) -> Result<Expr, anyhow::Error> {
+		if self.peek_name("not") {
+			self.pos += 1;
+			return Ok(Expr::Not(Box::new(self.parse_not()?)));
+		}
+
+		self.parse_comparison()
+	}
+
+	fn parse_comparison(&mut self) -> Result<Expr, anyhow::Error> {
+		let left = self.parse_bit_or()?;
+		let mut comparisons = Vec::new();
+
+		loop {
+			let op = if let Some(op) = self.match_op(&["==", "!=", "<=", ">=", "<", ">"]) {
+				op
+			} else if self.peek_name("in") {
+				self.pos += 1;
+				"in"
+			} else if self.peek_name("not") {
+				self.pos += 1;
+				if !self.peek_name("in") {
+					return Err(anyhow!("Expected 'in' after 'not'"));
+				}
+				self.pos += 1;
+				"not in"
+			} else if self.peek_name("is") {
+				self.pos += 1;
+				if self.peek_name("not") {
+					self.pos += 1;
+					"!="
+				} else {
+					"=="
+				}
+			} else {
+				break;
+			};
+
+			comparisons.push((op, self.parse_bit_or()?));
+		}
+
+		if comparisons.is_empty() {
+			Ok(left)
+		} else {
+			Ok(Expr::Compare(Box::new(left), comparisons))
+		}
+	}
+
+	/// parse left associative binary operators `ops`, with operands parsed by `operand`
+	fn parse_binary(
+		&mut self,
+		ops: &[&'static str],
+		operand: fn(&mut Self) -> Result<Expr, anyhow::Error>,
+	) -> Result<Expr, anyhow::Error> {
+		let mut left = operand(self)?;
+
+		while let Some(op) = self.match_op(ops) {
+			left = Expr::Binary(op, Box::new(left), Box::new(operand(self)?));
+		}
+
+		Ok(left)
+	}
+
+	fn parse_bit_or(&mut self) -> Result<Expr, anyhow::Error> {
+		self.parse_binary(&["|"], Self::parse_bit_xor)
+	}
+
+	fn parse_bit_xor(&mut self) -> Result<Expr, anyhow::Error> {
+		self.parse_binary(&["^"], Self::parse_bit_and)
+	}
+
+	fn parse_bit_and(&mut self) -> Result<Expr, anyhow::Error> {
+		self.parse_binary(&["&"], Self::parse_shift)
+	}
+
+	fn parse_shift(&mut self) -> Result<Expr, anyhow::Error> {
+		self.parse_binary(&["<<", ">>"], Self::parse_arithmetic)
+	}
+
+	fn parse_arithmetic(&mut self) -> Result<Expr, anyhow::Error> {
+		self.parse_binary(&["+", "-"], Self::parse_term)
+	}
+
+	fn parse_term(&mut self) -> Result<Expr, anyhow::Error> {
+		self.parse_binary(&["*", "//", "/", "%"], Self::parse_factor)
+	}
+
+	fn parse_factor(&mut self) -> Result<Expr, anyhow::Error> {
+		match self.match_op(&["-", "+", "~"]) {
+			Some(op) => Ok(Expr::Unary(op, Box::new(self.parse_factor()?))),
+			None => self.parse_power(),
+		}
+	}
+
+	fn parse_power(&mut self) -> Result<Expr, anyhow::Error> {
+		let base = self.parse_primary()?;
+
+		// the power operator is right associative and binds tighter than unary operators on its left
+		if self.match_op(&["**"]).is_some() {
+			return Ok(Expr::Binary(
+				"**",
+				Box::new(base),
+				Box::new(self.parse_factor()?),
+			));
+		}
+
+		Ok(base)
+	}
+
+	fn parse_primary(&mut self) -> Result<Expr, anyhow::Error> {
+		let mut expr = self.parse_atom()?;
+
+		loop {
+			if self.peek_op("(") {
+				let name = match expr {
+					Expr::Name(n) => n,
+					_ => return Err(anyhow!("Only named functions can be called")),
+				};
+				self.pos += 1;
+				expr = Expr::Call(name, self.parse_list(")")?);
+			} else if self.peek_op(".") {
+				self.pos += 1;
+				let method = match self.next()? {
+					Token::Name(n) => n,
+					t => return Err(anyhow!("Expected method name, found {}", describe(&t))),
+				};
+				self.expect_op("(")?;
+				expr = Expr::Method(Box::new(expr), method, self.parse_list(")")?);
+			} else if self.peek_op("[") {
+				self.pos += 1;
+				expr = self.parse_subscript(expr)?;
+			} else {
+				return Ok(expr);
+			}
+		}
+	}
+
+	fn parse_subscript(&mut self, expr: Expr) -> Result<Expr, anyhow::Error> {
+		let start = if self.peek_op(":") {
+			None
+		} else {
+			Some(Box::new(self.parse_arithmetic()?))
+		};
+
+		if self.match_op(&[":"]).is_none() {
+			self.expect_op("]")?;
+			return match start {
+				Some(index) => Ok(Expr::Index(Box::new(expr), index)),
+				None => Err(anyhow!("Missing index")),
+			};
+		}
+
+		let end = if self.peek_op("]") {
+			None
+		} else {
+			Some(Box::new(self.parse_arithmetic()?))
+		};
+
+		self.expect_op("]")?;
+		Ok(Expr::Slice(Box::new(expr), start, end))
+	}
+
+	/// parse comma separated expressions up to the `close` bracket
+	fn parse_list(&mut self, close: &str) -> Result<Vec<Expr>, anyhow::Error> {
+		let mut items = Vec::new();
+
+		while !self.peek_op(close) {
+			items.push(self.parse_or()?);
+
+			if !self.peek_op(close) {
+				self.expect_op(",")?;
+			}
+		}
+
+		self.expect_op(close)?;
+		Ok(items)
+	}
+
+	fn parse_atom(&mut self) -> Result<Expr, anyhow::Error> {
+		let position = self.tokens.get(self.pos).map(|(p, _)| *p).unwrap_or(0);
+
+		match self.next()? {
+			Token::Int(i) => Ok(Expr::Int(i)),
+			Token::Float(f) => Ok(Expr::Float(f)),
+			Token::Str(mut s) => {
+				// adjacent string literals are concatenated
+				while let Some(Token::Str(next)) = self.peek() {
+					s += next;
+					self.pos += 1;
+				}
+				Ok(Expr::Str(s))
+			}
+			Token::Name(n) => match n.as_str() {
+				"True" => Ok(Expr::Bool(true)),
+				"False" => Ok(Expr::Bool(false)),
+				"None" => Ok(Expr::None),
+				"and" | "or" | "not" | "in" | "is" => {
+					Err(anyhow!("Unexpected '{n}' at position {position}"))
+				}
+				n if UNSUPPORTED_KEYWORDS.contains(&n) => Err(anyhow!(
+					"'{n}' at position {position} is not supported in filter expressions"
+				)),
+				_ => Ok(Expr::Name(n)),
+			},
+			Token::Op("(") => {
+				let items = self.parse_list(")")?;
+				// a trailing comma makes a single element tuple
+				let trailing_comma = self.tokens[self.pos - 2].1 == Token::Op(",");
+
+				match items.len() {
+					1 if !trailing_comma => Ok(items.into_iter().next().unwrap()),
+					_ => Ok(Expr::List(items)),
+				}
+			}
+			Token::Op("[") => Ok(Expr::List(self.parse_list("]")?)),
+			t => Err(anyhow!(
+				"Unexpected {} at position {position}",
+				describe(&t)
+			)),
+		}
+	}
+}
+
+/// quote `s` as an evalexpr string literal
+fn quote(s: &str) -> String {
+	format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// format `f` so evalexpr reads it as a float
+fn float_literal(f: f64) -> String {
+	if f.fract() == 0.0 && f.abs() < 1e15 {
+		format!("{f:.1}")
+	} else {
+		format!("{f}")
+	}
+}
+
+/// emit the arguments of a function call
+fn emit_arguments(arguments: &[Expr]) -> Result<String, anyhow::Error> {
+	Ok(arguments
+		.iter()
+		.map(emit)
+		.collect::<Result<Vec<_>, _>>()?
+		.join(", "))
+}
+
+/// emit a membership test of `item` in `container`
+fn emit_in(item: &Expr, container: &Expr) -> Result<String, anyhow::Error> {
+	match container {
+		// evalexpr has no empty or single element tuples
+		Expr::List(items) if items.is_empty() => Ok("false".to_owned()),
+		Expr::List(items) if items.len() == 1 => {
+			Ok(format!("({} == {})", emit(item)?, emit(&items[0])?))
+		}
+		c => Ok(format!("in({}, {})", emit(item)?, emit(c)?)),
+	}
+}
+
+/// emit the evalexpr expression for `expr`
+fn emit(expr: &Expr) -> Result<String, anyhow::Error> {
+	match expr {
+		Expr::Name(n) => match FUNCTIONS.iter().find(|(f, _)| f == n) {
+			Some(_) => Err(anyhow!("Function '{n}' has to be called")),
+			None => Ok(n.to_owned()),
+		},
+		Expr::Int(i) => Ok(i.to_string()),
+		Expr::Float(f) => Ok(float_literal(*f)),
+		Expr::Str(s) => Ok(quote(s)),
+		Expr::Bool(b) => Ok(b.to_string()),
+		Expr::None => Ok("()".to_owned()),
+		Expr::List(items) => match items.len() {
+			0 | 1 => Err(anyhow!(
+				"Lists with less than two elements are only supported after 'in'"
+			)),
+			_ => Ok(format!("({})", emit_arguments(items)?)),
+		},
+		Expr::Unary(op, operand) => match *op {
+			"-" => Ok(format!("(-{})", emit(operand)?)),
+			"~" => Ok(format!("bitnot({})", emit(operand)?)),
+			_ => emit(operand),
+		},
+		Expr::Binary(op, left, right) => {
+			let (l, r) = (emit(left)?, emit(right)?);
+
+			match *op {
+				// python 3 division always results in a float
+				"/" => Ok(format!("({l} * 1.0 / {r})")),
+				"//" => Ok(format!("floor({l} * 1.0 / {r})")),
+				"**" => Ok(format!("pow({l}, {r})")),
+				"&" => Ok(format!("bitand({l}, {r})")),
+				"|" => Ok(format!("bitor({l}, {r})")),
+				"^" => Ok(format!("bitxor({l}, {r})")),
+				"<<" => Ok(format!("shl({l}, {r})")),
+				">>" => Ok(format!("shr({l}, {r})")),
+				op => Ok(format!("({l} {op} {r})")),
+			}
+		}
+		Expr::And(left, right) => Ok(format!("({} && {})", emit(left)?, emit(right)?)),
+		Expr::Or(left, right) => Ok(format!("({} || {})", emit(left)?, emit(right)?)),
+		Expr::Not(operand) => Ok(format!("!({})", emit(operand)?)),
+		Expr::Compare(first, comparisons) => {
+			let mut left = first.as_ref();
+			let mut emitted = Vec::new();
+
+			// chained comparisons like `a < b < c` are joined with `and`
+			for (op, right) in comparisons {
+				emitted.push(match *op {
+					"in" => emit_in(left, right)?,
+					"not in" => format!("!{}", emit_in(left, right)?),
+					op => format!("({} {op} {})", emit(left)?, emit(right)?),
+				});
+				left = right;
+			}
+
+			match emitted.len() {
+				1 => Ok(emitted.remove(0)),
+				_ => Ok(format!("({})", emitted.join(" && "))),
+			}
+		}
+		Expr::Call(name, arguments) => emit_call(name, arguments),
+		Expr::Method(object, name, arguments) => emit_method(object, name, arguments),
+		Expr::Index(object, index) => match object.as_ref() {
+			Expr::Method(string, method, arguments)
+				if method == "split" && arguments.len() == 1 =>
+			{
+				Ok(format!(
+					"split({}, {}, {})",
+					emit(string)?,
+					emit(&arguments[0])?,
+					emit(index)?
+				))
+			}
+			_ => Err(anyhow!(
+				"Indexing is only supported on the result of split(separator)"
+			)),
+		},
+		Expr::Slice(object, start, end) => {
+			let object = emit(object)?;
+
+			match (start.as_deref(), end.as_deref()) {
+				(start, None) => {
+					let start = start.map(emit).transpose()?;
+					Ok(format!(
+						"substr({object}, {})",
+						start.as_deref().unwrap_or("0")
+					))
+				}
+				(None, Some(Expr::Int(end))) if *end >= 0 => {
+					Ok(format!("substr({object}, 0, {end})"))
+				}
+				(Some(Expr::Int(start)), Some(Expr::Int(end))) if *start >= 0 && *end >= 0 => Ok(
+					format!("substr({object}, {start}, {})", (end - start).max(0)),
+				),
+				_ => Err(anyhow!(
+					"Slices are only supported with a positive end index"
+				)),
+			}
+		}
+	}
+}
+
+/// emit a call of the python function `name`
+fn emit_call(name: &str, arguments: &[Expr]) -> Result<String, anyhow::Error> {
+	let function = match FUNCTIONS.iter().find(|(f, _)| *f == name) {
+		Some((_, function)) => function,
+		None => {
+			let supported: Vec<&str> = FUNCTIONS.iter().map(|(f, _)| *f).collect();
+			return Err(anyhow!(
+				"Function '{name}' is not supported. Supported functions are: {}",
+				supported.join(", ")
+			));
+		}
+	};
+
+	match (name, arguments) {
+		// round to a number of digits
+		("round", [value, digits]) => {
+			let (value, digits) = (emit(value)?, emit(digits)?);
+			Ok(format!(
+				"(round({value} * pow(10, {digits})) * 1.0 / pow(10, {digits}))"
+			))
+		}
+		_ => Ok(format!("{function}({})", emit_arguments(arguments)?)),
+	}
+}
+
+/// emit a call of the python string method `name` on `object`
+fn emit_method(object: &Expr, name: &str, arguments: &[Expr]) -> Result<String, anyhow::Error> {
+	let object = emit(object)?;
+
+	match (name, arguments) {
+		("startswith" | "endswith", [argument]) => {
+			Ok(format!("{name}({object}, {})", emit(argument)?))
+		}
+		("lower" | "upper", []) => Ok(format!("{name}({object})")),
+		("strip", []) => Ok(format!("strip({object})")),
+		("strip", [chars]) => Ok(format!("strip({object}, {})", emit(chars)?)),
+		// python replaces literally, so the pattern is escaped
+		("replace", [Expr::Str(old), Expr::Str(new)]) => Ok(format!(
+			"replace({object}, {}, {})",
+			quote(&regex::escape(old)),
+			quote(&new.replace('$', "$$"))
+		)),
+		("replace", _) => Err(anyhow!(
+			"replace() is only supported with two string literals"
+		)),
+		("split", _) => Err(anyhow!(
+			"split(separator) is only supported when followed by an index"
+		)),
+		_ => Err(anyhow!(
+			"Method '{name}' with {} argument(s) is not supported. Supported methods are: startswith, endswith, lower, upper, strip, replace, split",
+			arguments.len()
+		)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn translates_galaxy_test_expressions() {
+		assert_eq!(
+			translate_python("c1=='chr22'").unwrap(),
+			r#"(c1 == "chr22")"#
+		);
+		assert_eq!(
+			translate_python("c1=='chr1' and c3-c2>=2000 and c6=='+'").unwrap(),
+			r#"(((c1 == "chr1") && ((c3 - c2) >= 2000)) && (c6 == "+"))"#
+		);
+		assert_eq!(
+			translate_python("c3=='chr1' and c5>5").unwrap(),
+			r#"((c3 == "chr1") && (c5 > 5))"#
+		);
+		assert_eq!(translate_python("c8>500").unwrap(), "(c8 > 500)");
+		assert_eq!(translate_python("c2=='100%'").unwrap(), r#"(c2 == "100%")"#);
+	}
+
+	#[test]
+	fn translates_boolean_operators() {
+		assert_eq!(
+			translate_python("c1=='chr1' and not c5 < 10").unwrap(),
+			r#"((c1 == "chr1") && !((c5 < 10)))"#
+		);
+		assert_eq!(
+			translate_python("c1 == 'chr1' or c1 == 'chr2' and c2 > 5").unwrap(),
+			r#"((c1 == "chr1") || ((c1 == "chr2") && (c2 > 5)))"#
+		);
+		assert_eq!(translate_python("c4 is True").unwrap(), "(c4 == true)");
+		assert_eq!(translate_python("c4 is not None").unwrap(), "(c4 != ())");
+	}
+
+	#[test]
+	fn translates_membership() {
+		assert_eq!(
+			translate_python("c4 in ['a','b']").unwrap(),
+			r#"in(c4, ("a", "b"))"#
+		);
+		assert_eq!(
+			translate_python("c4 not in ('a', \"b\")").unwrap(),
+			r#"!in(c4, ("a", "b"))"#
+		);
+		assert_eq!(translate_python("c4 in ['a']").unwrap(), r#"(c4 == "a")"#);
+		assert_eq!(translate_python("c4 in ('a',)").unwrap(), r#"(c4 == "a")"#);
+		assert_eq!(translate_python("c4 in []").unwrap(), "false");
+		assert_eq!(translate_python("'NM' in c4").unwrap(), r#"in("NM", c4)"#);
+	}
+
+	#[test]
+	fn translates_arithmetic() {
+		assert_eq!(
+			translate_python("c3 / c2 > 0.5").unwrap(),
+			"((c3 * 1.0 / c2) > 0.5)"
+		);
+		assert_eq!(
+			translate_python("c3 // 2 == 1").unwrap(),
+			"(floor(c3 * 1.0 / 2) == 1)"
+		);
+		assert_eq!(
+			translate_python("-c2 ** 2 < 1e3").unwrap(),
+			"((-pow(c2, 2)) < 1000.0)"
+		);
+		assert_eq!(
+			translate_python("c2 & 16 == 0").unwrap(),
+			"(bitand(c2, 16) == 0)"
+		);
+		assert_eq!(
+			translate_python("c2 | 4 << 1").unwrap(),
+			"bitor(c2, shl(4, 1))"
+		);
+		assert_eq!(
+			translate_python("1 < c5 <= 10").unwrap(),
+			"((1 < c5) && (c5 <= 10))"
+		);
+	}
+
+	#[test]
+	fn translates_functions_and_methods() {
+		assert_eq!(
+			translate_python("c2.startswith('x')").unwrap(),
+			r#"startswith(c2, "x")"#
+		);
+		assert_eq!(
+			translate_python("c4.lower() == 'a'").unwrap(),
+			r#"(lower(c4) == "a")"#
+		);
+		assert_eq!(
+			translate_python("c4.split('_')[1] == 'cds'").unwrap(),
+			r#"(split(c4, "_", 1) == "cds")"#
+		);
+		assert_eq!(
+			translate_python("c1[3:] == '22'").unwrap(),
+			r#"(substr(c1, 3) == "22")"#
+		);
+		assert_eq!(
+			translate_python("c1[0:3] == 'chr'").unwrap(),
+			r#"(substr(c1, 0, 3) == "chr")"#
+		);
+		assert_eq!(
+			translate_python("c4.replace('.', '_') == 'a_b'").unwrap(),
+			r#"(replace(c4, "\\.", "_") == "a_b")"#
+		);
+		assert_eq!(
+			translate_python("abs(c5 - c6) > 100 and float(c7) > 1.5").unwrap(),
+			"((abs((c5 - c6)) > 100) && (float(c7) > 1.5))"
+		);
+		assert_eq!(
+			translate_python("round(c5, 2) == 1.25").unwrap(),
+			"((round(c5 * pow(10, 2)) * 1.0 / pow(10, 2)) == 1.25)"
+		);
+	}
+
+	#[test]
+	fn quotes_strings() {
+		assert_eq!(
+			translate_python(r#"c1 == 'a"b'"#).unwrap(),
+			r#"(c1 == "a\"b")"#
+		);
+		assert_eq!(
+			translate_python(r"c1 == 'a\\b'").unwrap(),
+			r#"(c1 == "a\\b")"#
+		);
+		assert_eq!(
+			translate_python(r"c1 == 'a\tb'").unwrap(),
+			"(c1 == \"a\tb\")"
+		);
+	}
+
+	#[test]
+	fn rejects_unsupported_syntax() {
+		let error = |e: &str| translate_python(e).unwrap_err().to_string();
+
+		assert!(error("c1 = 'chr1'").contains("Unsupported character '='"));
+		assert!(error("__import__('os').system('ls')")
+			.contains("Function '__import__' is not supported"));
+		assert!(error("lambda: c1").contains("'lambda' at position 0 is not supported"));
+		assert!(error("c1 == 'chr1").contains("Unterminated string"));
+		assert!(error("c1 == 'chr1' c2").contains("Unexpected name 'c2' at position 13"));
+		assert!(error("c1.count('a') > 1").contains("Method 'count'"));
+		assert!(error("c1[0] == 'c'").contains("Indexing is only supported"));
+		assert!(error("c1 == ").contains("Unexpected end"));
+		assert!(error("c1 == {}").contains("Unsupported character '{'"));
+	}
+}
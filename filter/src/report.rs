@@ -0,0 +1,136 @@
+use serde::Serialize;
+use std::fmt::{self, Display};
+
+/// Counts of a filter run, written with `--report-json`.
+/// Field names are stable, to be read by workflow tooling
+#[derive(Serialize, Debug, Default)]
+pub struct FilterReport {
+	/// Amount of read lines, including skipped and invalid lines
+	pub total_lines: usize,
+
+	/// Lines which are neither comments nor blank
+	pub valid_lines: usize,
+
+	/// Lines written to any of the outputs, including skipped header lines
+	pub kept_lines: usize,
+
+	/// Valid lines passing no expression
+	pub rejected_lines: usize,
+
+	/// Blank lines, and comment lines not written to the outputs
+	pub skipped_lines: usize,
+
+	/// Comment lines written to the outputs
+	pub comment_lines: usize,
+
+	/// Lines which could not be evaluated
+	pub invalid_lines: usize,
+
+	pub first_invalid_line: Option<usize>,
+	pub first_invalid_content: Option<String>,
+
+	/// Lines kept in every output, in the order of the expressions
+	pub outputs: Vec<OutputReport>,
+
+	/// Types inferred for columns without declared type
+	pub inferred_types: Vec<InferredType>,
+
+	pub elapsed_seconds: f64,
+
+	#[serde(skip)]
+	pub keep_comments: bool,
+
+	#[serde(skip)]
+	pub comment_prefixes: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct OutputReport {
+	pub file: String,
+	pub expression: String,
+	pub kept_lines: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct InferredType {
+	/// Column identifier, e.g. `c1`
+	pub column: String,
+
+	#[serde(rename = "type")]
+	pub column_type: String,
+}
+
+/// the report printed after filtering
+impl Display for FilterReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.valid_lines > 0 {
+			writeln!(
+				f,
+				"Kept {:.2}% of {} valid lines ({} total lines)",
+				100.0 * self.kept_lines as f64 / self.valid_lines as f64,
+				self.valid_lines,
+				self.total_lines
+			)?;
+
+			if self.outputs.len() > 1 {
+				for output in &self.outputs {
+					writeln!(f, "Kept {} line(s) in '{}'", output.kept_lines, output.file)?;
+				}
+
+				writeln!(f, "{} line(s) passed no expression", self.rejected_lines)?;
+			}
+		} else {
+			writeln!(
+				f,
+				"No lines kept. Check filter condition '{}', see tool tips, syntax and examples",
+				self.outputs
+					.iter()
+					.map(|o| o.expression.as_str())
+					.collect::<Vec<_>>()
+					.join("', '")
+			)?;
+		}
+
+		if let (Some(line), Some(content)) = (self.first_invalid_line, &self.first_invalid_content)
+		{
+			writeln!(
+				f,
+				"Skipped {} invalid line(s) starting at line {line}: '{content}'",
+				self.invalid_lines
+			)?;
+		}
+
+		let prefixes = self.comment_prefixes.join(" or ");
+
+		if self.comment_lines > 0 {
+			writeln!(
+				f,
+				"Kept {} comment line(s) (starting with {prefixes})",
+				self.comment_lines
+			)?;
+		}
+
+		if self.skipped_lines > 0 && self.keep_comments {
+			writeln!(f, "Skipped {} blank line(s)", self.skipped_lines)?;
+		} else if self.skipped_lines > 0 {
+			writeln!(
+				f,
+				"Skipped {} comment (starting with {prefixes}) or blank line(s)",
+				self.skipped_lines
+			)?;
+		}
+
+		if !self.inferred_types.is_empty() {
+			let inferred = self
+				.inferred_types
+				.iter()
+				.map(|t| format!("{}={}", t.column, t.column_type))
+				.collect::<Vec<_>>()
+				.join(", ");
+
+			writeln!(f, "Inferred column types: {inferred}")?;
+		}
+
+		Ok(())
+	}
+}
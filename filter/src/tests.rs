@@ -1,4 +1,5 @@
 use std::fs::{create_dir_all, remove_dir_all};
+use std::io::Read;
 
 use super::*;
 use test_utils::*;
@@ -12,7 +13,6 @@ fn simple_string_equality() {
 		in_file: "../test_data/1.bed".to_owned(),
 		out_file: format!("{tmp}/out1.bed"),
 		expression: r#"c1=="chr22""#.to_owned(),
-		skip_lines: 0,
 		types: vec![
 			ColumnType::Str,
 			ColumnType::Int,
@@ -21,6 +21,7 @@ fn simple_string_equality() {
 			ColumnType::Int,
 			ColumnType::Str,
 		],
+		..Default::default()
 	};
 
 	let result = run_with_args(&args);
@@ -42,7 +43,6 @@ fn complex_expression() {
 		in_file: "../test_data/7.bed".to_owned(),
 		out_file: format!("{tmp}/out2.bed"),
 		expression: r#"c1=="chr1" && c3-c2>=2000 && c6=="+""#.to_owned(),
-		skip_lines: 0,
 		types: vec![
 			ColumnType::Str,
 			ColumnType::Int,
@@ -56,6 +56,7 @@ fn complex_expression() {
 			ColumnType::Int,
 			ColumnType::Str,
 		],
+		..Default::default()
 	};
 
 	let result = run_with_args(&args);
@@ -77,7 +78,6 @@ fn large_file() {
 		in_file: "../test_data/filter1_in3.sam".to_owned(),
 		out_file: format!("{tmp}/out3.sam"),
 		expression: r#"c3=="chr1" && c5>5"#.to_owned(),
-		skip_lines: 0,
 		types: vec![
 			ColumnType::Str,
 			ColumnType::Int,
@@ -93,6 +93,7 @@ fn large_file() {
 			ColumnType::Str,
 			ColumnType::Str,
 		],
+		..Default::default()
 	};
 
 	let result = run_with_args(&args);
@@ -114,7 +115,6 @@ fn bad_input() {
 		in_file: "../test_data/filter1_inbad.bed".to_owned(),
 		out_file: format!("{tmp}/out4.bed"),
 		expression: r#"c1=="chr22""#.to_owned(),
-		skip_lines: 0,
 		types: vec![
 			ColumnType::Str,
 			ColumnType::Int,
@@ -123,6 +123,7 @@ fn bad_input() {
 			ColumnType::Int,
 			ColumnType::Str,
 		],
+		..Default::default()
 	};
 
 	let result = run_with_args(&args);
@@ -164,6 +165,7 @@ fn removes_header() {
 			ColumnType::Float,
 			ColumnType::Str,
 		],
+		..Default::default()
 	};
 
 	let result = run_with_args(&args);
@@ -185,7 +187,6 @@ fn mixed_type() {
 		in_file: "../test_data/filter1_in6.bed".to_owned(),
 		out_file: format!("{tmp}/out6.bed"),
 		expression: r#"c2=="100%""#.to_owned(),
-		skip_lines: 0,
 		types: vec![
 			ColumnType::Str,
 			ColumnType::Str,
@@ -194,6 +195,7 @@ fn mixed_type() {
 			ColumnType::Int,
 			ColumnType::Str,
 		],
+		..Default::default()
 	};
 
 	let result = run_with_args(&args);
@@ -205,3 +207,1556 @@ fn mixed_type() {
 
 	remove_dir_all(tmp).unwrap();
 }
+
+fn cufflinks_types() -> Vec<ColumnType> {
+	vec![
+		ColumnType::Str,
+		ColumnType::Str,
+		ColumnType::Str,
+		ColumnType::Str,
+		ColumnType::Str,
+		ColumnType::Str,
+		ColumnType::Str,
+		ColumnType::Int,
+		ColumnType::Str,
+		ColumnType::Int,
+		ColumnType::Int,
+		ColumnType::Int,
+		ColumnType::Str,
+		ColumnType::Float,
+		ColumnType::Int,
+		ColumnType::Float,
+		ColumnType::Str,
+	]
+}
+
+#[test]
+fn header_names() {
+	let tmp = ".tmp/7";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/filter1_in5.tab".to_owned(),
+		out_file: format!("{tmp}/out7.tab"),
+		expression: r#"length>500"#.to_owned(),
+		types: cufflinks_types(),
+		header: true,
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(is_file_equal(
+		&args.out_file,
+		"../test_data/filter1_test5.tab"
+	));
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn quoted_header_names() {
+	let tmp = ".tmp/8";
+	create_dir_all(tmp).unwrap();
+
+	let positional = Arguments {
+		in_file: "../test_data/filter1_in5.tab".to_owned(),
+		out_file: format!("{tmp}/positional.tab"),
+		expression: r#"c14>500.0 && c8>300"#.to_owned(),
+		skip_lines: 1,
+		types: cufflinks_types(),
+		..Default::default()
+	};
+
+	let named = Arguments {
+		in_file: "../test_data/filter1_in5.tab".to_owned(),
+		out_file: format!("{tmp}/named.tab"),
+		expression: r#"`replicate 2_FPKM`>500.0 && length>300"#.to_owned(),
+		types: cufflinks_types(),
+		header: true,
+		..Default::default()
+	};
+
+	let result = run_with_args(&positional);
+	assert!(result.is_ok(), "{:?}", result);
+
+	let result = run_with_args(&named);
+	assert!(result.is_ok(), "{:?}", result);
+
+	assert!(is_file_equal(&positional.out_file, &named.out_file));
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn unknown_header_name() {
+	let tmp = ".tmp/9";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/filter1_in5.tab".to_owned(),
+		out_file: format!("{tmp}/out9.tab"),
+		expression: r#"lenght>500"#.to_owned(),
+		types: cufflinks_types(),
+		header: true,
+		..Default::default()
+	};
+
+	let error = run_with_args(&args).unwrap_err().to_string();
+	assert!(error.contains("Unknown column 'lenght'"), "{error}");
+	assert!(error.contains("tracking_id, class_code"), "{error}");
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn inferred_types() {
+	let tmp = ".tmp/10";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/filter1_in5.tab".to_owned(),
+		out_file: format!("{tmp}/out10.tab"),
+		expression: r#"c8>500"#.to_owned(),
+		skip_lines: 1,
+		infer_lines: 100,
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(is_file_equal(
+		&args.out_file,
+		"../test_data/filter1_test5.tab"
+	));
+
+	let report = result.unwrap();
+	assert!(
+		report.contains("Inferred column types: c1=str,"),
+		"{report}"
+	);
+	assert!(report.contains("c8=int, c9=str, c10=int"), "{report}");
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn inference_without_lines() {
+	let tmp = ".tmp/41";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in41.tsv");
+	std::fs::write(&in_file, "").unwrap();
+
+	// columns not in the sample are empty, unless types are declared
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out41.tsv"),
+		expression: r#"c1 == "a" || c3 == "x""#.to_owned(),
+		infer_lines: 100,
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(result.unwrap().contains("No lines kept"));
+
+	std::fs::write(&args.in_file, "a\n").unwrap();
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(std::fs::read_to_string(&args.out_file).unwrap(), "a\n");
+
+	args.types = vec![ColumnType::Str];
+	assert!(run_with_args(&args).is_err());
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn explicit_types_override_inference() {
+	let tmp = ".tmp/11";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/out11.bed"),
+		expression: r#"c1=="chr22""#.to_owned(),
+		types: vec![ColumnType::Str, ColumnType::Str],
+		infer_lines: 100,
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(is_file_equal(
+		&args.out_file,
+		"../test_data/filter1_test1.bed"
+	));
+
+	let report = result.unwrap();
+	assert!(
+		report.contains("Inferred column types: c3=int, c4=str, c5=int, c6=str\n"),
+		"{report}"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn regex_expression() {
+	let tmp = ".tmp/12";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/7.bed".to_owned(),
+		out_file: format!("{tmp}/out12.bed"),
+		expression: r#"matches(c4, "^NM_")"#.to_owned(),
+		types: vec![ColumnType::Str; 12],
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		result.unwrap(),
+		"Kept 82.76% of 29 valid lines (29 total lines)\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn invalid_regex() {
+	let tmp = ".tmp/13";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/7.bed".to_owned(),
+		out_file: format!("{tmp}/out13.bed"),
+		expression: r#"matches(c4, "^NM_(")"#.to_owned(),
+		types: vec![ColumnType::Str; 12],
+		..Default::default()
+	};
+
+	let error = run_with_args(&args).unwrap_err().to_string();
+	assert!(error.contains("Expression test failed"), "{error}");
+	assert!(
+		error.contains("Invalid regular expression '^NM_('"),
+		"{error}"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn string_functions() {
+	let tmp = ".tmp/14";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/out14.bed"),
+		expression: r#"startswith(c4, "CCDS") && len(c4) == 35"#.to_owned(),
+		types: vec![ColumnType::Str; 6],
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		result.unwrap(),
+		"Kept 36.92% of 65 valid lines (65 total lines)\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn math_functions() {
+	let tmp = ".tmp/15";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/7.bed".to_owned(),
+		out_file: format!("{tmp}/out15.bed"),
+		expression: r#"log10(abs(c2 - c3)) > 4.5 && min(c2, c7) == c2"#.to_owned(),
+		types: vec![
+			ColumnType::Str,
+			ColumnType::Int,
+			ColumnType::Int,
+			ColumnType::Str,
+			ColumnType::Int,
+			ColumnType::Str,
+			ColumnType::Int,
+		],
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		result.unwrap(),
+		"Kept 48.28% of 29 valid lines (29 total lines)\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn galaxy_python_syntax() {
+	let tmp = ".tmp/16";
+	create_dir_all(tmp).unwrap();
+
+	use ColumnType::{Int, Str};
+
+	// test cases of Galaxy's Filter1 tool
+	let cases = [
+		("1.bed", "c1=='chr22'", 0, "filter1_test1.bed"),
+		(
+			"7.bed",
+			"c1=='chr1' and c3-c2>=2000 and c6=='+'",
+			0,
+			"filter1_test2.bed",
+		),
+		(
+			"filter1_in3.sam",
+			"c3=='chr1' and c5>5",
+			0,
+			"filter1_test3.sam",
+		),
+		("filter1_inbad.bed", "c1=='chr22'", 0, "filter1_test4.bed"),
+		("filter1_in5.tab", "c8>500", 1, "filter1_test5.tab"),
+		("filter1_in6.bed", "c2=='100%'", 0, "filter1_test6.bed"),
+	];
+
+	for (i, (in_file, expression, skip_lines, expected)) in cases.into_iter().enumerate() {
+		let types = match in_file {
+			"filter1_in3.sam" => vec![Str, Int, Str, Int, Int],
+			"filter1_in5.tab" => cufflinks_types(),
+			"filter1_in6.bed" => vec![Str, Str],
+			_ => vec![Str, Int, Int, Str, Int, Str],
+		};
+
+		let args = Arguments {
+			in_file: format!("../test_data/{in_file}"),
+			out_file: format!("{tmp}/out{i}"),
+			expression: expression.to_owned(),
+			skip_lines,
+			types,
+			syntax: ExpressionSyntax::Python,
+			..Default::default()
+		};
+
+		let result = run_with_args(&args);
+		assert!(result.is_ok(), "{expression}: {:?}", result);
+		assert!(
+			is_file_equal(&args.out_file, &format!("../test_data/{expected}")),
+			"{expression}"
+		);
+	}
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn invalid_python_syntax() {
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: ".tmp/17/out.bed".to_owned(),
+		expression: "c1 = 'chr22'".to_owned(),
+		syntax: ExpressionSyntax::Python,
+		..Default::default()
+	};
+
+	let error = run_with_args(&args).unwrap_err().to_string();
+	assert!(error.contains("Unsupported character '='"), "{error}");
+}
+
+#[test]
+fn columns_in_string_literals() {
+	let tmp = ".tmp/18";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/out18.bed"),
+		expression: r#"c1=="chr22" && c4 != "c9_calc2""#.to_owned(),
+		types: vec![ColumnType::Str; 6],
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(is_file_equal(
+		&args.out_file,
+		"../test_data/filter1_test1.bed"
+	));
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn column_zero() {
+	let tmp = ".tmp/19";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/out19.bed"),
+		expression: r#"c1 == "c0" || c0 > 5"#.to_owned(),
+		types: vec![ColumnType::Str; 6],
+		..Default::default()
+	};
+
+	let error = run_with_args(&args).unwrap_err().to_string();
+	assert_eq!(
+		error,
+		"Column numbers start at 1, 'c0' does not exist\n c1 == \"c0\" || c0 > 5\n               ^"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn column_without_type() {
+	let tmp = ".tmp/20";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/out20.bed"),
+		expression: r#"c1 == "chr1" && c12 > 5"#.to_owned(),
+		types: vec![ColumnType::Str; 6],
+		..Default::default()
+	};
+
+	let error = run_with_args(&args).unwrap_err().to_string();
+	assert!(error.starts_with("Column 'c12' is used, but only 6 column type(s) are known"));
+	assert!(
+		error.ends_with("\n c1 == \"chr1\" && c12 > 5\n                 ^"),
+		"{error}"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn unknown_identifier() {
+	let tmp = ".tmp/21";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/out21.bed"),
+		expression: r#"calc2 > 5"#.to_owned(),
+		types: vec![ColumnType::Str; 6],
+		..Default::default()
+	};
+
+	let error = run_with_args(&args).unwrap_err().to_string();
+	assert!(error.contains("Expression test failed"), "{error}");
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn rejected_and_invalid_files() {
+	let tmp = ".tmp/22";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/filter1_inbad.bed".to_owned(),
+		out_file: format!("{tmp}/out22.bed"),
+		expression: r#"c6=="+" && c2 > 0"#.to_owned(),
+		types: vec![
+			ColumnType::Str,
+			ColumnType::Int,
+			ColumnType::Int,
+			ColumnType::Str,
+			ColumnType::Int,
+			ColumnType::Str,
+		],
+		rejected_file: Some(format!("{tmp}/rejected22.bed")),
+		invalid_file: Some(format!("{tmp}/invalid22.tsv")),
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+
+	let read = |path: &str| std::fs::read_to_string(path).unwrap();
+
+	assert_eq!(
+		read(&args.out_file),
+		"chr22\t30120223\t30120265\tCCDS13897.1_cds_0_0_chr22_30120224_f\t0\t+\n\
+		chr22\t30665273\t30665360\tCCDS13901.1_cds_0_0_chr22_30665274_f\t0\t+\n"
+	);
+	assert_eq!(
+		read(args.rejected_file.as_ref().unwrap()),
+		"chr22\t30160419\t30160661\tCCDS13898.1_cds_0_0_chr22_30160420_r\t0\t-\n\
+		chr22\t30939054\t30939266\tCCDS13903.1_cds_0_0_chr22_30939055_r\t0\t-\n"
+	);
+	assert_eq!(
+		read(args.invalid_file.as_ref().unwrap()),
+		"3\tColumn c2: 'foo' is not a valid int\tchr22\tfoo\tfoo\tfoo\tfoo\tfoo\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn partitions() {
+	let tmp = ".tmp/23";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/filter1_inbad.bed".to_owned(),
+		out_file: format!("{tmp}/out23.bed"),
+		expression: "c2 < 30150000".to_owned(),
+		types: vec![
+			ColumnType::Str,
+			ColumnType::Int,
+			ColumnType::Int,
+			ColumnType::Str,
+			ColumnType::Int,
+			ColumnType::Str,
+		],
+		partition: vec![
+			(r#"c6=="+""#.to_owned(), format!("{tmp}/plus23.bed")),
+			("c3 - c2 == 242".to_owned(), format!("{tmp}/short23.bed")),
+		],
+		rejected_file: Some(format!("{tmp}/else23.bed")),
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+
+	let report = result.unwrap();
+	assert!(report.contains(&format!("Kept 1 line(s) in '{tmp}/out23.bed'\n")));
+	assert!(report.contains(&format!("Kept 1 line(s) in '{tmp}/plus23.bed'\n")));
+	assert!(report.contains(&format!("Kept 1 line(s) in '{tmp}/short23.bed'\n")));
+	assert!(report.contains("1 line(s) passed no expression\n"));
+
+	let read = |path: &str| std::fs::read_to_string(path).unwrap();
+
+	// lines are only written to the first partition they pass
+	assert_eq!(
+		read(&args.out_file),
+		"chr22\t30120223\t30120265\tCCDS13897.1_cds_0_0_chr22_30120224_f\t0\t+\n"
+	);
+	assert_eq!(
+		read(&args.partition[0].1),
+		"chr22\t30665273\t30665360\tCCDS13901.1_cds_0_0_chr22_30665274_f\t0\t+\n"
+	);
+	assert_eq!(
+		read(&args.partition[1].1),
+		"chr22\t30160419\t30160661\tCCDS13898.1_cds_0_0_chr22_30160420_r\t0\t-\n"
+	);
+	assert_eq!(
+		read(args.rejected_file.as_ref().unwrap()),
+		"chr22\t30939054\t30939266\tCCDS13903.1_cds_0_0_chr22_30939055_r\t0\t-\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn partition_argument() {
+	assert_eq!(
+		parse_partition("c1==\"chr1\"=out.bed").unwrap(),
+		("c1==\"chr1\"".to_owned(), "out.bed".to_owned())
+	);
+	assert!(parse_partition("c1 > 5").is_err());
+	assert!(parse_partition("c1 > 5=").is_err());
+}
+
+#[test]
+fn keep_comments() {
+	let tmp = ".tmp/24";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/filter1_in7.vcf".to_owned(),
+		out_file: format!("{tmp}/out24.vcf"),
+		expression: r#"c7=="PASS""#.to_owned(),
+		infer_lines: 100,
+		keep_comments: true,
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		result.unwrap(),
+		"Kept 66.67% of 3 valid lines (7 total lines)\n\
+		Kept 3 comment line(s) (starting with #)\n\
+		Skipped 1 blank line(s)\n\
+		Inferred column types: c1=int, c2=int, c3=str, c4=str, c5=str, c6=int, c7=str, c8=str\n"
+	);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"##fileformat=VCFv4.2\n\
+		##source=example\n\
+		#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+		20\t14370\trs6054257\tG\tA\t29\tPASS\tDP=14\n\
+		20\t1110696\trs6040355\tA\tG\t67\tPASS\tDP=10\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn comment_prefixes() {
+	let tmp = ".tmp/25";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/filter1_in8.bed".to_owned(),
+		out_file: format!("{tmp}/out25.bed"),
+		expression: r#"c6=="+""#.to_owned(),
+		infer_lines: 100,
+		comment_prefixes: Some(vec![
+			"#".to_owned(),
+			"track".to_owned(),
+			"browser".to_owned(),
+		]),
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(result
+		.unwrap()
+		.contains("Skipped 3 comment (starting with # or track or browser) or blank line(s)\n"));
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"chr22\t30120223\t30120265\tCCDS13897.1\t0\t+\n\
+		chr22\t30665273\t30665360\tCCDS13901.1\t0\t+\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn threads_preserve_output() {
+	let tmp = ".tmp/26";
+	create_dir_all(tmp).unwrap();
+
+	let run = |threads: usize| {
+		let args = Arguments {
+			in_file: "../test_data/filter1_inbad.bed".to_owned(),
+			out_file: format!("{tmp}/out26_{threads}.bed"),
+			expression: r#"matches(c4, "_f$") || c3 - c2 > 240"#.to_owned(),
+			types: vec![
+				ColumnType::Str,
+				ColumnType::Int,
+				ColumnType::Int,
+				ColumnType::Str,
+				ColumnType::Int,
+				ColumnType::Str,
+			],
+			rejected_file: Some(format!("{tmp}/rejected26_{threads}.bed")),
+			invalid_file: Some(format!("{tmp}/invalid26_{threads}.tsv")),
+			threads,
+			..Default::default()
+		};
+
+		let result = run_with_args(&args);
+		assert!(result.is_ok(), "{:?}", result);
+
+		result.unwrap()
+	};
+
+	let sequential = run(1);
+
+	for threads in [2, 4, 16] {
+		assert_eq!(run(threads), sequential);
+
+		for file in ["out26", "rejected26"] {
+			assert!(is_file_equal(
+				&format!("{tmp}/{file}_{threads}.bed"),
+				&format!("{tmp}/{file}_1.bed")
+			));
+		}
+
+		assert!(is_file_equal(
+			&format!("{tmp}/invalid26_{threads}.tsv"),
+			&format!("{tmp}/invalid26_1.tsv")
+		));
+	}
+
+	remove_dir_all(tmp).unwrap();
+}
+
+/// filter like before byte buffers: a `String` for every line, split into a `Vec`,
+/// and written with a newline appended. Returns the number of kept lines
+fn filter_with_strings(
+	in_file: &str,
+	out_file: &str,
+	expression: &str,
+	column_types: &[ColumnType],
+) -> usize {
+	use evalexpr::ContextWithMutableVariables;
+	use std::io::{BufRead, BufReader, BufWriter};
+
+	let node = build_operator_tree(expression).unwrap();
+	let columns = get_used_columns(&node, expression).unwrap();
+	let mut context = crate::functions::create_function_context();
+	let reader = BufReader::new(std::fs::File::open(in_file).unwrap());
+	let mut writer = BufWriter::new(std::fs::File::create(out_file).unwrap());
+	let mut kept = 0;
+
+	for line in reader.lines() {
+		let line = line.unwrap();
+		let values: Vec<&str> = line.split('\t').collect();
+
+		for column in &columns {
+			let value = parse_value(values[column.index], &column_types[column.index], ",");
+			context
+				.set_value(column.name.clone(), value.unwrap())
+				.unwrap();
+		}
+
+		if node.eval_boolean_with_context(&context).unwrap() {
+			writer.write_all(format!("{line}\n").as_bytes()).unwrap();
+			kept += 1;
+		}
+	}
+
+	writer.flush().unwrap();
+	kept
+}
+
+/// compares the throughput with the line processing using strings,
+/// run with `cargo test -r -- --ignored`
+#[test]
+#[ignore]
+fn large_file_throughput() {
+	let tmp = ".tmp/27";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in27.bed");
+	let lines = 100_000;
+
+	{
+		let mut writer = create_writer(&in_file, None).unwrap();
+
+		for i in 0..lines {
+			let strand = if i % 3 == 0 { "-" } else { "+" };
+			writeln!(
+				writer,
+				"chr{}\t{}\t{}\tfeature_{i}\t{}\t{strand}\t{}\t{}\t0\t1\t{}\t0",
+				i % 22 + 1,
+				i * 100,
+				i * 100 + i % 500,
+				i % 1000,
+				i * 100,
+				i * 100 + 50,
+				i % 500
+			)
+			.unwrap();
+		}
+
+		io_utils::finish(writer).unwrap();
+	}
+
+	let expression = r#"c6 == "+" && c3 - c2 > 100"#;
+	let mut types = vec![ColumnType::Str; 12];
+	types[1] = ColumnType::Int;
+	types[2] = ColumnType::Int;
+
+	let args = Arguments {
+		in_file: in_file.clone(),
+		out_file: format!("{tmp}/out27.bed"),
+		expression: expression.to_owned(),
+		types: types.clone(),
+		..Default::default()
+	};
+
+	let start = std::time::Instant::now();
+	let result = run_with_args(&args);
+	let elapsed = start.elapsed();
+
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(result
+		.unwrap()
+		.starts_with("Kept 53.20% of 100000 valid lines (100000 total lines)\n"));
+
+	let strings_file = format!("{tmp}/out27_strings.bed");
+	let start = std::time::Instant::now();
+	let kept = filter_with_strings(&in_file, &strings_file, expression, &types);
+	let strings_elapsed = start.elapsed();
+
+	assert_eq!(kept, lines * 532 / 1000);
+	assert!(is_file_equal(&args.out_file, &strings_file));
+	assert!(
+		elapsed < strings_elapsed,
+		"byte buffers took {elapsed:?}, strings took {strings_elapsed:?}"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn compressed_files() {
+	let tmp = ".tmp/28";
+	create_dir_all(tmp).unwrap();
+
+	let read = |path: &str| {
+		let mut contents = String::new();
+		io_utils::open(path)
+			.unwrap()
+			.read_to_string(&mut contents)
+			.unwrap();
+
+		contents
+	};
+
+	let in_file = format!("{tmp}/in28.bed.gz");
+	let mut writer = create_writer(&in_file, Some(Compression::Gzip)).unwrap();
+	writer
+		.write_all(read("../test_data/1.bed").as_bytes())
+		.unwrap();
+	io_utils::finish(writer).unwrap();
+
+	let args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out28.bed.gz"),
+		expression: r#"c1=="chr22""#.to_owned(),
+		types: vec![ColumnType::Str],
+		rejected_file: Some(format!("{tmp}/rejected28.bed.zst")),
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+
+	// outputs are compressed by their extension
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap()[..4],
+		[0x1f, 0x8b, 0x08, 0x04]
+	);
+	assert_eq!(read(&args.out_file), read("../test_data/filter1_test1.bed"));
+	assert_eq!(
+		read(args.rejected_file.as_ref().unwrap()).lines().count(),
+		65 - read(&args.out_file).lines().count()
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn any_reader_and_writer() {
+	let tmp = ".tmp/29";
+	create_dir_all(tmp).unwrap();
+
+	let out_file = format!("{tmp}/out29.bed");
+	let mut input = std::io::Cursor::new("chr1\t5\nchr2\t50\n# comment\nchr3\t500\n");
+	let mut writer =
+		io_utils::write_to(std::fs::File::create(&out_file).unwrap(), Compression::None).unwrap();
+
+	let options = FilterOptions {
+		column_types: vec![ColumnType::Str, ColumnType::Int],
+		..Default::default()
+	};
+
+	let result = filter_with_expression(
+		&mut input,
+		&mut writer,
+		&"c2 > 10".to_owned(),
+		&options,
+		None,
+		None,
+	);
+	io_utils::finish(writer).unwrap();
+
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		result.unwrap().to_string(),
+		"Kept 66.67% of 3 valid lines (4 total lines)\n\
+		Skipped 1 comment (starting with #) or blank line(s)\n"
+	);
+	assert_eq!(
+		std::fs::read_to_string(&out_file).unwrap(),
+		"chr2\t50\nchr3\t500\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn report_destination() {
+	let args = Arguments {
+		out_file: "out.bed".to_owned(),
+		rejected_file: Some("rejected.bed".to_owned()),
+		..Default::default()
+	};
+
+	assert!(!args.writes_to_stdout());
+
+	let to_stdout = [
+		Arguments {
+			out_file: "-".to_owned(),
+			..Default::default()
+		},
+		Arguments {
+			out_file: "out.bed".to_owned(),
+			rejected_file: Some("-".to_owned()),
+			..Default::default()
+		},
+		Arguments {
+			out_file: "out.bed".to_owned(),
+			partition: vec![("c1 > 5".to_owned(), "-".to_owned())],
+			..Default::default()
+		},
+	];
+
+	for args in to_stdout {
+		assert!(args.writes_to_stdout());
+	}
+}
+
+#[test]
+fn non_utf8_input() {
+	let tmp = ".tmp/30";
+	create_dir_all(tmp).unwrap();
+
+	// "Müller" and "Zoë" encoded as Latin-1
+	let in_file = format!("{tmp}/in30.tsv");
+	let input = b"1\tM\xfcller\n2\tZo\xeb\n3\tSmith\n";
+	std::fs::write(&in_file, input).unwrap();
+
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out30.tsv"),
+		expression: "c1 > 1".to_owned(),
+		types: vec![ColumnType::Int, ColumnType::Str],
+		..Default::default()
+	};
+
+	// unused columns are written unchanged
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"2\tZo\xeb\n3\tSmith\n"
+	);
+
+	// invalid UTF-8 is replaced when decoding
+	args.expression = "c2 == \"Zo\u{FFFD}\"".to_owned();
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(std::fs::read(&args.out_file).unwrap(), b"2\tZo\xeb\n");
+
+	args.expression = r#"c2 == "Müller" || endswith(c2, "ë")"#.to_owned();
+	args.encoding = Encoding::Latin1;
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"1\tM\xfcller\n2\tZo\xeb\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn line_endings() {
+	let tmp = ".tmp/31";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in31.bed");
+	let input = b"track name=test\r\nchr1\t100\t+\r\nchr2\t200\t-\r\nchr3\t300\t+";
+	std::fs::write(&in_file, input).unwrap();
+
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out31.bed"),
+		expression: r#"c3 == "+""#.to_owned(),
+		skip_lines: 1,
+		infer_lines: 100,
+		..Default::default()
+	};
+
+	// `\r` is not part of the last column, and is written back with the line
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"track name=test\r\nchr1\t100\t+\r\nchr3\t300\t+"
+	);
+
+	// only the output of the last line misses the final newline
+	args.expression = r#"c3 == "-""#.to_owned();
+	args.rejected_file = Some(format!("{tmp}/rejected31.bed"));
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"track name=test\r\nchr2\t200\t-\r\n"
+	);
+	assert_eq!(
+		std::fs::read(args.rejected_file.as_ref().unwrap()).unwrap(),
+		b"track name=test\r\nchr1\t100\t+\r\nchr3\t300\t+"
+	);
+
+	args.expression = r#"c3 == "+""#.to_owned();
+	args.rejected_file = None;
+	args.normalize_eol = true;
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"track name=test\nchr1\t100\t+\nchr3\t300\t+\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn delimiters() {
+	let tmp = ".tmp/32";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in32.csv");
+	let input = "gene,description,score\nBRCA1,\"DNA repair, \"\"breast cancer\"\"\",5\nTP53,tumor suppressor,3\n";
+	std::fs::write(&in_file, input).unwrap();
+
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out32.csv"),
+		expression: r#"score > 2 && in(",", description)"#.to_owned(),
+		header: true,
+		infer_lines: 100,
+		delimiter: Delimiter::Comma,
+		..Default::default()
+	};
+
+	// quoted fields are unquoted for the expression, but written unchanged
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"gene,description,score\nBRCA1,\"DNA repair, \"\"breast cancer\"\"\",5\n"
+	);
+
+	args.in_file = format!("{tmp}/in32.txt");
+	args.expression = r#"c2 == "tumor""#.to_owned();
+	args.header = false;
+	args.delimiter = Delimiter::Whitespace;
+	std::fs::write(&args.in_file, "BRCA1   DNA  5\n  TP53 tumor 3\n").unwrap();
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"  TP53 tumor 3\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn report_json() {
+	let tmp = ".tmp/33";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in33.tsv");
+	std::fs::write(
+		&in_file,
+		"# comment\nchr1\t5\nchr2\tx\n\nchr3\t500\nchr4\t7\n",
+	)
+	.unwrap();
+
+	let args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out33.tsv"),
+		expression: "c2 > 10".to_owned(),
+		partition: vec![("c2 > 6".to_owned(), format!("{tmp}/medium33.tsv"))],
+		types: vec![ColumnType::Str, ColumnType::Int],
+		report_json: Some(format!("{tmp}/report33.json")),
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+
+	let report: serde_json::Value =
+		serde_json::from_str(&std::fs::read_to_string(args.report_json.unwrap()).unwrap()).unwrap();
+
+	assert_eq!(report["total_lines"], 6);
+	assert_eq!(report["valid_lines"], 4);
+	assert_eq!(report["kept_lines"], 2);
+	assert_eq!(report["rejected_lines"], 1);
+	assert_eq!(report["skipped_lines"], 2);
+	assert_eq!(report["invalid_lines"], 1);
+	assert_eq!(report["first_invalid_line"], 2);
+	assert_eq!(report["first_invalid_content"], "chr2\tx");
+	assert_eq!(report["outputs"][0]["kept_lines"], 1);
+	assert_eq!(report["outputs"][1]["file"], format!("{tmp}/medium33.tsv"));
+	assert_eq!(report["outputs"][1]["expression"], "c2 > 6");
+	assert!(report["elapsed_seconds"].is_f64());
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn explain() {
+	let tmp = ".tmp/34";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/out34.bed"),
+		expression: r#"c1=="chr22" && c5 > 500"#.to_owned(),
+		types: vec![ColumnType::Str],
+		infer_lines: 100,
+		explain: Some(2),
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+
+	let explanation = result.unwrap();
+	assert!(explanation.starts_with("Expression: c1==\"chr22\" && c5 > 500\nOperator tree:\n"));
+	assert!(explanation.contains("VariableIdentifierRead { identifier: \"c5\" }"));
+	assert!(explanation.contains("  c1 (column 1): str (declared)\n"));
+	assert!(explanation.contains("  c5 (column 5): int (inferred)\n"));
+	assert!(explanation.contains("Line 1:\n  c1 = \"chr1\"\n"));
+	assert!(explanation
+		.ends_with("Line 2:\n  c1 = \"chr1\"\n  c5 = 0\n  c1==\"chr22\" && c5 > 500 -> false\n"));
+
+	// nothing is written in explain mode
+	assert!(!std::path::Path::new(&args.out_file).exists());
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn typed_lists() {
+	let tmp = ".tmp/35";
+	create_dir_all(tmp).unwrap();
+
+	// blockSizes and blockStarts of BED12 are inferred as lists of ints
+	let mut args = Arguments {
+		in_file: "../test_data/7.bed".to_owned(),
+		out_file: format!("{tmp}/out35.bed"),
+		expression: r#"sum(c11) > 3000 && any_cmp(c11, ">", 1000) && len(c11) == c10"#.to_owned(),
+		infer_lines: 100,
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+
+	let report = result.unwrap();
+	assert!(
+		report.starts_with("Kept 34.48% of 29 valid lines"),
+		"{report}"
+	);
+	assert!(report.contains("c11=list:int, c12=list:int"), "{report}");
+
+	// element types and separators can be declared
+	args.in_file = format!("{tmp}/in35.tsv");
+	args.expression = "in(2, c2) && last(c2) > 2.5".to_owned();
+	args.types = vec!["str".parse().unwrap(), "list:float".parse().unwrap()];
+	args.list_separator = Some(";".to_owned());
+	std::fs::write(&args.in_file, "a\t1;2;3\nb\t4;5\nc\t2\n").unwrap();
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"a\t1;2;3\n"
+	);
+
+	assert!("list:list".parse::<ColumnType>().is_err());
+	assert!(Arguments::try_parse_from([
+		"filter",
+		"-i",
+		"-",
+		"-o",
+		"-",
+		"-e",
+		"true",
+		"--list-separator",
+		""
+	])
+	.is_err());
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn date_columns() {
+	let tmp = ".tmp/36";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in36.tsv");
+	let input = "s1\t2023-12-31\t31.12.2023 23:00\n\
+		s2\t2024-01-15\t15.01.2024 08:30\n\
+		s3\tunknown\t01.02.2024 10:00\n\
+		s4\t2024-03-01\t01.03.2024 12:00\n";
+	std::fs::write(&in_file, input).unwrap();
+
+	let args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out36.tsv"),
+		expression: r#"c2 >= date("2024-01-01") && month(c3) < 3 && days_between(c2, c3) == 0"#
+			.to_owned(),
+		types: vec![
+			"str".parse().unwrap(),
+			"date".parse().unwrap(),
+			"datetime:%d.%m.%Y %H:%M".parse().unwrap(),
+		],
+		..Default::default()
+	};
+
+	// unparseable dates are invalid lines
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(result
+		.unwrap()
+		.contains("Skipped 1 invalid line(s) starting at line 2: 's3\tunknown\t01.02.2024 10:00'"));
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"s2\t2024-01-15\t15.01.2024 08:30\n"
+	);
+
+	// formats which can not be parsed to a date
+	assert!("date:%Y-%m".parse::<ColumnType>().is_err());
+	assert!("datetime:%Y-%m-%d".parse::<ColumnType>().is_err());
+	assert!("date:%Q".parse::<ColumnType>().is_err());
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn interval_functions() {
+	let tmp = ".tmp/37";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in37.bed");
+	let input = "chr1\t99\t150\tgene1\n\
+		chr1\t200\t300\tgene2\n\
+		chr2\t120\t130\tgene3\n\
+		chr1\t1000\t1100\tgene4\n";
+	std::fs::write(&in_file, input).unwrap();
+
+	let args = Arguments {
+		in_file: in_file.clone(),
+		out_file: format!("{tmp}/out37.bed"),
+		expression: r#"overlaps(c1, c2, c3, "chr1:100-200") || coalesce(distance(c1, c2, c3, region("chr1:1-900")), 200) < 200"#.to_owned(),
+		types: vec![
+			"str".parse().unwrap(),
+			"int".parse().unwrap(),
+			"int".parse().unwrap(),
+		],
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"chr1\t99\t150\tgene1\n\
+		chr1\t200\t300\tgene2\n\
+		chr1\t1000\t1100\tgene4\n"
+	);
+
+	// the same columns read as 1-based coordinates
+	let args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out37_gff.bed"),
+		expression:
+			r#"within(c1, c2, c3, "chr1:100-300", "gff") && interval_len(c2, c3, "gff") > 60"#
+				.to_owned(),
+		types: vec![
+			"str".parse().unwrap(),
+			"int".parse().unwrap(),
+			"int".parse().unwrap(),
+		],
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"chr1\t200\t300\tgene2\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn line_variables() {
+	let tmp = ".tmp/38";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in38.tsv");
+	let input = "name\tsize\tnote\n\
+		a\t10\tok\n\
+		b\t20\n\
+		c\t30\tFAILED check\n\
+		d\t40\tok\n";
+	std::fs::write(&in_file, input).unwrap();
+
+	// malformed rows are dropped, and a pattern is matched across all columns
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out38.tsv"),
+		expression: r#"NR > 1 && NF == 3 && !matches(line, "FAIL")"#.to_owned(),
+		types: vec![ColumnType::Str],
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"a\t10\tok\nd\t40\tok\n"
+	);
+
+	// header columns take precedence over line variables
+	std::fs::write(&args.in_file, "line\tNF\n1\t2\n3\t4\n").unwrap();
+	args.expression = "line + NF == 7 && NR == 3".to_owned();
+	args.types = vec![ColumnType::Int, ColumnType::Int];
+	args.header = true;
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"line\tNF\n3\t4\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn column_ranges() {
+	let tmp = ".tmp/39";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in39.tsv");
+	let input = "g1\tchr1\t0\t7.5\t1\t4\t2\t3\n\
+		g2\tchr1\t0\t0\t0\t0\t1\t0\n\
+		g3\tchr2\t6\t6\t6\t6\t6\t6\n\
+		g4\tchr2\t1\t2\t3\t0\t0\t0\n";
+	std::fs::write(&in_file, input).unwrap();
+
+	// the last type is repeated up to the last column used in the expression
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out39.tsv"),
+		expression: "any(c3..c8 > 5) && count(c3..c8 > 0) >= 5".to_owned(),
+		types: "str,str,float*"
+			.split(',')
+			.map(|t| t.parse().unwrap())
+			.collect(),
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"g1\tchr1\t0\t7.5\t1\t4\t2\t3\ng3\tchr2\t6\t6\t6\t6\t6\t6\n"
+	);
+
+	// ranges of a single column and comparisons as arguments
+	args.expression = r#"count_cmp(c3..c8, ">", 0) == 3 || all(c3..c3 >= 6)"#.to_owned();
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"g3\tchr2\t6\t6\t6\t6\t6\t6\ng4\tchr2\t1\t2\t3\t0\t0\t0\n"
+	);
+
+	// functions inside list functions are applied to every column
+	args.expression = "any(log10(c3..c8) > 0.8)".to_owned();
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"g1\tchr1\t0\t7.5\t1\t4\t2\t3\n"
+	);
+
+	args.expression = "c3..c8 > 5".to_owned();
+	let result = run_with_args(&args);
+	assert!(result
+		.unwrap_err()
+		.to_string()
+		.starts_with("Column range 'c3..c8' can only be used in the argument of a list function"));
+
+	args.expression = "abs(c3..c8) > 5".to_owned();
+	assert!(run_with_args(&args).is_err());
+
+	args.expression = "any(c3..c8 > c4..c5)".to_owned();
+	assert!(run_with_args(&args).is_err());
+
+	args.expression = "any(c8..c3 > 5)".to_owned();
+	assert!(run_with_args(&args).is_err());
+
+	args.expression = "any(c3..c8 > 5)".to_owned();
+	args.types = vec![
+		ColumnType::Repeated(Box::new(ColumnType::Float)),
+		ColumnType::Str,
+	];
+	assert!(run_with_args(&args).is_err());
+
+	assert!("float**".parse::<ColumnType>().is_err());
+	assert_eq!(
+		"list:int*".parse::<ColumnType>().unwrap().to_string(),
+		"list:int*"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn expands_column_ranges() {
+	assert_eq!(
+		expand_column_ranges(r#"any(c1..c3 > 5) && c4 == "c1..c3""#).unwrap(),
+		r#"any((c1 > 5, c2 > 5, c3 > 5)) && c4 == "c1..c3""#
+	);
+	assert_eq!(
+		expand_column_ranges("all(c1..c2 < max(c3..c4))").unwrap(),
+		"all((c1 < max((c3, c4)), c2 < max((c3, c4))))"
+	);
+	assert_eq!(
+		expand_column_ranges("count(c1..c2 == c3..c4) > 1").unwrap(),
+		"count((c1 == c3, c2 == c4)) > 1"
+	);
+	assert_eq!(
+		expand_column_ranges("any(abs(c1..c3) > 5)").unwrap(),
+		"any((abs(c1) > 5, abs(c2) > 5, abs(c3) > 5))"
+	);
+	assert_eq!(
+		expand_column_ranges("any(math::log(c1..c2, 10) > 2)").unwrap(),
+		"any((math::log(c1, 10) > 2, math::log(c2, 10) > 2))"
+	);
+	assert!(expand_column_ranges("(c1..c2)").is_err());
+	assert!(expand_column_ranges("abs(c1..c2)").is_err());
+}
+
+#[test]
+fn na_values() {
+	let tmp = ".tmp/40";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in40.vcf");
+	let input = "chr1\t100\t.\t50.5\n\
+		chr1\t200\trs1\t.\n\
+		chr1\t300\trs2\tNA\n\
+		chr1\t400\trs3\t20\n";
+	std::fs::write(&in_file, input).unwrap();
+
+	// the quality column is inferred as float, despite missing values
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out40.vcf"),
+		expression: "coalesce(c4, 0.0) < 30.0 && !is_na(c3)".to_owned(),
+		na_values: Some(vec![".".to_owned(), "NA".to_owned()]),
+		infer_lines: 100,
+		..Default::default()
+	};
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(result.unwrap().contains("c4=float"));
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"chr1\t200\trs1\t.\n\
+		chr1\t300\trs2\tNA\n\
+		chr1\t400\trs3\t20\n"
+	);
+
+	// comparisons with missing values make the line invalid by default
+	args.expression = "c4 > 10.0".to_owned();
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(result
+		.unwrap()
+		.contains("Skipped 2 invalid line(s) starting at line 1"));
+
+	// or are false
+	args.na_comparisons = NaComparisons::False;
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(!result.unwrap().contains("invalid"));
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"chr1\t100\t.\t50.5\nchr1\t400\trs3\t20\n"
+	);
+
+	// also for comparisons which evaluate without an error
+	args.expression = "c4 != 20.0".to_owned();
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"chr1\t100\t.\t50.5\n"
+	);
+
+	args.na_comparisons = NaComparisons::Invalid;
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(result
+		.unwrap()
+		.contains("Skipped 2 invalid line(s) starting at line 1"));
+
+	// is_na works as guard, and the other side of || decides, under both policies
+	for na_comparisons in [NaComparisons::Invalid, NaComparisons::False] {
+		args.na_comparisons = na_comparisons;
+
+		let cases = [
+			(
+				"is_na(c4) || c4 > 30.0",
+				"chr1\t100\t.\t50.5\nchr1\t200\trs1\t.\nchr1\t300\trs2\tNA\n",
+			),
+			(
+				r#"c3 == "rs1" || c4 > 30.0"#,
+				"chr1\t100\t.\t50.5\nchr1\t200\trs1\t.\n",
+			),
+		];
+
+		for (expression, output) in cases {
+			args.expression = expression.to_owned();
+
+			let result = run_with_args(&args);
+			assert!(result.is_ok(), "{:?}", result);
+			assert_eq!(
+				std::fs::read_to_string(&args.out_file).unwrap(),
+				output,
+				"{expression}"
+			);
+		}
+	}
+
+	// errors unrelated to the missing value are not hidden
+	args.expression = "c4 > 10.0 || 1 / (c2 - 200) > 0".to_owned();
+	args.na_comparisons = NaComparisons::False;
+
+	let result = run_with_args(&args);
+	assert!(result.is_ok(), "{:?}", result);
+	assert!(result
+		.unwrap()
+		.contains("Skipped 1 invalid line(s) starting at line 1"));
+	assert_eq!(
+		std::fs::read_to_string(&args.out_file).unwrap(),
+		"chr1\t100\t.\t50.5\nchr1\t400\trs3\t20\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
@@ -1,24 +1,24 @@
-use std::{
-	collections::HashMap,
-	fs::File,
-	io::{BufRead, BufReader, BufWriter, Write},
-	process::exit,
-};
+use std::{borrow::Cow, collections::HashMap, io::Write, process::exit, time::Instant};
 
 use anyhow::anyhow;
 use clap::Parser;
 use indexmap::IndexMap;
+use io_utils::{output_ending, Compression, Delimiter, LineBuffer};
 use operations::{Operation, OperationFunction};
+use serde::Serialize;
 
 mod operations;
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Parser)]
 pub struct Arguments {
-	/// File to be grouped
+	/// File to be grouped. Use `-` to read from stdin
 	#[arg(short, long)]
 	in_file: String,
 
-	/// File to write to
+	/// File to write to. Use `-` to write to stdout
 	#[arg(short, long)]
 	out_file: String,
 
@@ -27,7 +27,7 @@ pub struct Arguments {
 	group_by: usize,
 
 	/// Whether to ignore case when grouping
-	#[arg(short, long)]
+	#[arg(long)]
 	ignore_case: bool,
 
 	/// Comma separated list of ascii characters. Rows starting with any of the characters will be ignored
@@ -37,6 +37,38 @@ pub struct Arguments {
 	/// Operations to run separated by a space. Format: operation,column,round_result,(optional)default_value
 	#[arg(visible_alias = "ops", visible_alias = "op", long, value_parser = parse_operation, num_args = 0.., value_delimiter = ' ')]
 	operations: Vec<OperationFunction>,
+
+	/// Compression of the output file. By default it is selected by its extension.
+	/// Compressed input files are detected automatically
+	#[arg(long, value_enum)]
+	compression: Option<Compression>,
+
+	/// Write all lines with LF line endings.
+	/// By default, lines are written with the line ending of the input, and without a final
+	/// newline if the input has none
+	#[arg(long)]
+	normalize_eol: bool,
+
+	/// Separator between columns: `tab`, `comma` (with quoted fields as in CSV files),
+	/// `whitespace` or any single character
+	#[arg(long, default_value = "tab")]
+	delimiter: Delimiter,
+
+	/// Separator between columns of the output. Defaults to `delimiter`
+	#[arg(long)]
+	output_delimiter: Option<Delimiter>,
+
+	/// File to write the counts of the run to, as JSON
+	#[arg(long)]
+	report_json: Option<String>,
+}
+
+/// Counts of a run, written with `--report-json`
+#[derive(Serialize)]
+struct Report {
+	total_lines: usize,
+	groups: usize,
+	elapsed_seconds: f64,
 }
 
 trait ToOptionF64 {
@@ -99,6 +131,8 @@ fn main() {
 	let args = Arguments::parse();
 
 	match run_with_args(&args) {
+		// the report must not end up in the output
+		Ok(report) if io_utils::is_stdio(&args.out_file) => eprintln!("{report}"),
 		Ok(report) => println!("{report}"),
 		Err(e) => {
 			eprintln!("{e}");
@@ -108,31 +142,38 @@ fn main() {
 }
 
 pub fn run_with_args(args: &Arguments) -> Result<String, anyhow::Error> {
-	let input_file = File::open(&args.in_file)?;
-	let output_file = File::create(&args.out_file)?;
-
-	let reader = BufReader::new(input_file);
-	let mut writer = BufWriter::new(output_file);
+	let start = Instant::now();
+	let mut reader = io_utils::open(&args.in_file)?;
+	let mut writer = io_utils::create(&args.out_file, args.compression)?;
 
 	let columns_used: Vec<usize> = args.operations.iter().map(|op_fn| op_fn.col).collect();
-	let mut groups: IndexMap<String, Group> = IndexMap::new();
+	let mut groups: IndexMap<Vec<u8>, Group> = IndexMap::new();
+	let mut buffer = LineBuffer::default();
+
+	// values are kept as raw bytes, so any encoding is written unchanged
+	loop {
+		let line_number = buffer.lines_read();
+
+		let line = match buffer.read_line(&mut reader)? {
+			Some(l) => l,
+			None => break,
+		};
 
-	for (line_number, line) in reader.lines().enumerate() {
-		let line = line?;
-		let values: Vec<&str> = line.split("\t").collect();
+		let values: Vec<Cow<[u8]>> = args.delimiter.split(line).collect();
 
-		let group_val = values
-			.get(args.group_by - 1)
-			.ok_or(anyhow!(
-				"Grouping column {} not defined on line {line_number}",
-				args.group_by - 1
-			))?
-			.to_owned();
+		let group_val = values.get(args.group_by - 1).ok_or(anyhow!(
+			"Grouping column {} not defined on line {line_number}",
+			args.group_by - 1
+		))?;
 
 		let group_val = if args.ignore_case {
-			group_val.to_lowercase()
+			// only ascii letters can be lowercased without decoding
+			match std::str::from_utf8(group_val) {
+				Ok(s) => s.to_lowercase().into_bytes(),
+				Err(_) => group_val.to_ascii_lowercase(),
+			}
 		} else {
-			group_val.to_owned()
+			group_val.to_vec()
 		};
 
 		let group = groups.entry(group_val).or_insert(Group {
@@ -142,15 +183,20 @@ pub fn run_with_args(args: &Arguments) -> Result<String, anyhow::Error> {
 		for col in &columns_used {
 			let column = group.columns.entry(*col).or_insert(Vec::new());
 			let val = match values.get(*col) {
-				Some(s) => s,
-				None => "",
+				Some(s) => &s[..],
+				None => &b""[..],
 			};
 
 			column.push(val.to_owned());
 		}
+
+		buffer.clear();
 	}
 
-	for (key, group) in &groups {
+	let ending = output_ending(buffer.line_ending(), args.normalize_eol);
+	let output_delimiter = args.output_delimiter.unwrap_or(args.delimiter);
+
+	for (i, (key, group)) in groups.iter().enumerate() {
 		let mut outputs = Vec::new();
 		outputs.push(key.to_owned());
 
@@ -163,14 +209,30 @@ pub fn run_with_args(args: &Arguments) -> Result<String, anyhow::Error> {
 			outputs.push(output);
 		}
 
-		writer.write(&format!("{}\n", outputs.join("\t")).into_bytes())?;
+		writer.write_all(&output_delimiter.join(&outputs))?;
+
+		let last = i + 1 == groups.len();
+
+		if !last || buffer.ends_with_newline() || args.normalize_eol {
+			writer.write_all(ending)?;
+		}
 	}
 
-	writer.flush()?;
+	io_utils::finish(writer)?;
+
+	if let Some(file) = &args.report_json {
+		let report = Report {
+			total_lines: buffer.lines_read(),
+			groups: groups.len(),
+			elapsed_seconds: start.elapsed().as_secs_f64(),
+		};
+
+		io_utils::write_json_report(file, &report)?;
+	}
 
 	Ok(format!("Grouped into {} lines", groups.len()))
 }
 
 struct Group {
-	columns: HashMap<usize, Vec<String>>,
+	columns: HashMap<usize, Vec<Vec<u8>>>,
 }
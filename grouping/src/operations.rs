@@ -7,12 +7,17 @@ trait ToF64Vec {
 	fn to_f64_vec(&self, default: Option<f64>) -> Vec<f64>;
 }
 
-impl ToF64Vec for Vec<String> {
+impl ToF64Vec for Vec<Vec<u8>> {
 	fn to_f64_vec(&self, default: Option<f64>) -> Vec<f64> {
 		let default = default.unwrap_or(f64::NAN);
 
 		self.iter()
-			.map(|s| s.parse::<f64>().unwrap_or(default))
+			.map(|s| {
+				std::str::from_utf8(s)
+					.ok()
+					.and_then(|s| s.parse::<f64>().ok())
+					.unwrap_or(default)
+			})
 			.filter(|f| !f.is_nan())
 			.collect()
 	}
@@ -55,8 +60,8 @@ pub enum Operation {
 }
 
 impl Operation {
-	fn run(&self, values: &Vec<String>, round: bool, default: Option<f64>) -> String {
-		match self {
+	fn run(&self, values: &Vec<Vec<u8>>, round: bool, default: Option<f64>) -> Vec<u8> {
+		let text = match self {
 			Operation::Mean => {
 				let total: f64 = values.to_f64_vec(default).into_iter().sum();
 
@@ -129,20 +134,23 @@ impl Operation {
 
 				set.len().to_string_round(round)
 			}
-			Operation::Concatenate => values.join(","),
+			// values are returned unchanged, without decoding them
+			Operation::Concatenate => return values.join(&b','),
 			Operation::ConcatenateDistinct => {
 				let mut set = IndexSet::new();
 
 				for val in values {
-					set.insert(val.to_owned());
+					set.insert(val.as_slice());
 				}
 
-				set.into_iter().collect::<Vec<_>>().join(",")
+				return set.into_iter().collect::<Vec<_>>().join(&b',');
+			}
+			Operation::Random => {
+				return values
+					.choose(&mut rand::thread_rng())
+					.expect("Failed to choose random value. Received no values")
+					.to_owned()
 			}
-			Operation::Random => values
-				.choose(&mut rand::thread_rng())
-				.expect("Failed to choose random value. Received no values")
-				.to_owned(),
 			Operation::StandardDeviation => {
 				let values = values.to_f64_vec(default);
 				let count = values.len() as f64;
@@ -152,7 +160,9 @@ impl Operation {
 
 				f64::sqrt((sum2 / count) - (mean * mean)).to_string_round(round)
 			}
-		}
+		};
+
+		text.into_bytes()
 	}
 }
 
@@ -165,7 +175,7 @@ pub struct OperationFunction {
 }
 
 impl OperationFunction {
-	pub fn run_operation(&self, values: &Vec<String>) -> String {
+	pub fn run_operation(&self, values: &Vec<Vec<u8>>) -> Vec<u8> {
 		self.op.run(&values, self.round, self.default)
 	}
 }
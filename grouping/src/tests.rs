@@ -0,0 +1,196 @@
+use std::fs::{create_dir_all, read_to_string, remove_dir_all};
+use std::io::Read;
+
+use super::*;
+
+#[test]
+fn compressed_files() {
+	let tmp = ".tmp/1";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in.bed.gz");
+	let mut writer = io_utils::create(&in_file, None).unwrap();
+	writer
+		.write_all(read_to_string("../test_data/1.bed").unwrap().as_bytes())
+		.unwrap();
+	io_utils::finish(writer).unwrap();
+
+	let args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out.tabular.zst"),
+		group_by: 1,
+		ignore_case: false,
+		delete_rows: Vec::new(),
+		operations: vec![parse_operation("length,2,false").unwrap()],
+		compression: None,
+		normalize_eol: false,
+		delimiter: Delimiter::Tab,
+		output_delimiter: None,
+		report_json: None,
+	};
+
+	let res = run_with_args(&args);
+	assert!(res.is_ok(), "{:?}", res);
+	assert_eq!(res.unwrap(), "Grouped into 20 lines");
+
+	let mut output = String::new();
+	io_utils::open(&args.out_file)
+		.unwrap()
+		.read_to_string(&mut output)
+		.unwrap();
+
+	assert!(output.starts_with("chr1\t4\nchr10\t1\nchr11\t4\n"));
+	assert_eq!(output.lines().count(), 20);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn non_utf8_input() {
+	let tmp = ".tmp/2";
+	create_dir_all(tmp).unwrap();
+
+	// group names and values encoded as Latin-1
+	let in_file = format!("{tmp}/in.tabular");
+	std::fs::write(
+		&in_file,
+		b"M\xfcller\t1\tA\xe9\nSmith\t2\tB\nM\xfcller\t3\tC\n",
+	)
+	.unwrap();
+
+	let args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out.tabular"),
+		group_by: 1,
+		ignore_case: true,
+		delete_rows: Vec::new(),
+		operations: vec![
+			parse_operation("sum,2,false").unwrap(),
+			parse_operation("cat,3,false").unwrap(),
+		],
+		compression: None,
+		normalize_eol: false,
+		delimiter: Delimiter::Tab,
+		output_delimiter: None,
+		report_json: None,
+	};
+
+	let res = run_with_args(&args);
+	assert!(res.is_ok(), "{:?}", res);
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"m\xfcller\t4\tA\xe9,C\nsmith\t2\tB\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn line_endings() {
+	let tmp = ".tmp/3";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in.tabular");
+	std::fs::write(&in_file, b"a\t1\r\nb\t2\r\na\t3").unwrap();
+
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out.tabular"),
+		group_by: 1,
+		ignore_case: false,
+		delete_rows: Vec::new(),
+		operations: vec![parse_operation("sum,2,false").unwrap()],
+		compression: None,
+		normalize_eol: false,
+		delimiter: Delimiter::Tab,
+		output_delimiter: None,
+		report_json: None,
+	};
+
+	// the value in the last column does not contain `\r`
+	let res = run_with_args(&args);
+	assert!(res.is_ok(), "{:?}", res);
+	assert_eq!(std::fs::read(&args.out_file).unwrap(), b"a\t4\r\nb\t2");
+
+	args.normalize_eol = true;
+
+	let res = run_with_args(&args);
+	assert!(res.is_ok(), "{:?}", res);
+	assert_eq!(std::fs::read(&args.out_file).unwrap(), b"a\t4\nb\t2\n");
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn delimiters() {
+	let tmp = ".tmp/4";
+	create_dir_all(tmp).unwrap();
+
+	let in_file = format!("{tmp}/in.csv");
+	std::fs::write(&in_file, "\"a, b\",x\nc,y\n\"a, b\",\"z\"\"\"\n").unwrap();
+
+	let mut args = Arguments {
+		in_file,
+		out_file: format!("{tmp}/out.csv"),
+		group_by: 1,
+		ignore_case: false,
+		delete_rows: Vec::new(),
+		operations: vec![parse_operation("cat,2,false").unwrap()],
+		compression: None,
+		normalize_eol: false,
+		delimiter: Delimiter::Comma,
+		output_delimiter: None,
+		report_json: None,
+	};
+
+	// the output keeps the dialect of the input
+	let res = run_with_args(&args);
+	assert!(res.is_ok(), "{:?}", res);
+	assert_eq!(
+		read_to_string(&args.out_file).unwrap(),
+		"\"a, b\",\"x,z\"\"\"\nc,y\n"
+	);
+
+	args.output_delimiter = Some(Delimiter::Tab);
+
+	let res = run_with_args(&args);
+	assert!(res.is_ok(), "{:?}", res);
+	assert_eq!(
+		read_to_string(&args.out_file).unwrap(),
+		"a, b\tx,z\"\nc\ty\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn report_json() {
+	let tmp = ".tmp/5";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/out.tabular"),
+		group_by: 1,
+		ignore_case: false,
+		delete_rows: Vec::new(),
+		operations: vec![parse_operation("length,2,false").unwrap()],
+		compression: None,
+		normalize_eol: false,
+		delimiter: Delimiter::Tab,
+		output_delimiter: None,
+		report_json: Some(format!("{tmp}/report.json")),
+	};
+
+	let res = run_with_args(&args);
+	assert!(res.is_ok(), "{:?}", res);
+
+	let report: serde_json::Value =
+		serde_json::from_str(&read_to_string(args.report_json.unwrap()).unwrap()).unwrap();
+
+	assert_eq!(report["total_lines"], 65);
+	assert_eq!(report["groups"], 20);
+	assert!(report["elapsed_seconds"].is_f64());
+
+	remove_dir_all(tmp).unwrap();
+}
@@ -0,0 +1,110 @@
+use flate2::{Compress, Compression, Crc, FlushCompress, Status};
+use std::io::{self, Write};
+
+/// Maximum amount of uncompressed data in one block, same as used by bgzip
+const BLOCK_SIZE: usize = 0xff00;
+
+/// Maximum size of a compressed block, including header and footer
+const MAX_COMPRESSED_SIZE: usize = 0x10000;
+
+/// Empty block marking the end of a bgzip file
+const EOF_BLOCK: [u8; 28] = [
+	0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+	0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Writes the blocked gzip format (BGZF) of bgzip.
+/// Every block is a separate gzip member storing its compressed size, which lets tabix index the file
+pub struct BgzfWriter<W: Write> {
+	inner: W,
+
+	/// Uncompressed data of the current block
+	block: Vec<u8>,
+
+	/// Compressed data of the current block
+	compressed: Vec<u8>,
+
+	compress: Compress,
+}
+
+impl<W: Write> BgzfWriter<W> {
+	pub fn new(inner: W) -> Self {
+		BgzfWriter {
+			inner,
+			block: Vec::with_capacity(BLOCK_SIZE),
+			compressed: Vec::with_capacity(MAX_COMPRESSED_SIZE),
+			// blocks contain raw deflate data, without zlib header
+			compress: Compress::new(Compression::default(), false),
+		}
+	}
+
+	/// compress the current block and write it to `inner`
+	fn write_block(&mut self) -> io::Result<()> {
+		let mut crc = Crc::new();
+		crc.update(&self.block);
+
+		self.compress.reset();
+		self.compressed.clear();
+
+		let status = self
+			.compress
+			.compress_vec(&self.block, &mut self.compressed, FlushCompress::Finish)
+			.map_err(io::Error::other)?;
+
+		// header, compressed data, crc and uncompressed size
+		let block_size = 18 + self.compressed.len() + 8;
+
+		if status != Status::StreamEnd || block_size > MAX_COMPRESSED_SIZE {
+			return Err(io::Error::other("Failed to compress bgzip block"));
+		}
+
+		let mut header = [
+			0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43,
+			0x02, 0x00, 0x00, 0x00,
+		];
+		header[16..].copy_from_slice(&((block_size - 1) as u16).to_le_bytes());
+
+		self.inner.write_all(&header)?;
+		self.inner.write_all(&self.compressed)?;
+		self.inner.write_all(&crc.sum().to_le_bytes())?;
+		self.inner
+			.write_all(&(self.block.len() as u32).to_le_bytes())?;
+
+		self.block.clear();
+
+		Ok(())
+	}
+
+	/// write the remaining data and the end of file marker
+	pub fn finish(mut self) -> io::Result<W> {
+		if !self.block.is_empty() {
+			self.write_block()?;
+		}
+
+		self.inner.write_all(&EOF_BLOCK)?;
+		self.inner.flush()?;
+
+		Ok(self.inner)
+	}
+}
+
+impl<W: Write> Write for BgzfWriter<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let len = buf.len().min(BLOCK_SIZE - self.block.len());
+		self.block.extend_from_slice(&buf[..len]);
+
+		if self.block.len() == BLOCK_SIZE {
+			self.write_block()?;
+		}
+
+		Ok(len)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		if !self.block.is_empty() {
+			self.write_block()?;
+		}
+
+		self.inner.flush()
+	}
+}
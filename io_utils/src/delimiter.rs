@@ -0,0 +1,167 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// Separator between the fields of a line
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Delimiter {
+	#[default]
+	Tab,
+	/// Comma, with fields quoted as described in RFC 4180.
+	/// Quoted fields can not contain line breaks
+	Comma,
+	/// Runs of spaces and tabs. Leading and trailing whitespace is ignored
+	Whitespace,
+	/// Any single ascii character
+	Char(u8),
+}
+
+impl FromStr for Delimiter {
+	type Err = String;
+
+	/// parse `tab`, `comma`, `whitespace` or a single ascii character
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"tab" | "\t" => Ok(Delimiter::Tab),
+			"comma" | "," => Ok(Delimiter::Comma),
+			"whitespace" => Ok(Delimiter::Whitespace),
+			_ if s.len() == 1 && s.is_ascii() => Ok(Delimiter::Char(s.as_bytes()[0])),
+			_ => Err(format!(
+				"Expected tab, comma, whitespace or a single ascii character, got '{s}'"
+			)),
+		}
+	}
+}
+
+impl Delimiter {
+	/// split `line` into its fields.
+	/// Quoted fields are returned without quotes
+	pub fn split<'a>(&self, line: &'a [u8]) -> Fields<'a> {
+		Fields {
+			rest: Some(line),
+			delimiter: *self,
+		}
+	}
+
+	/// join `fields` into a line, quoting fields where needed
+	pub fn join(&self, fields: &[impl AsRef<[u8]>]) -> Vec<u8> {
+		let separator = match self {
+			Delimiter::Tab => b'\t',
+			Delimiter::Comma => b',',
+			Delimiter::Whitespace => b' ',
+			Delimiter::Char(c) => *c,
+		};
+
+		let mut line = Vec::new();
+
+		for (i, field) in fields.iter().enumerate() {
+			let field = field.as_ref();
+
+			if i > 0 {
+				line.push(separator);
+			}
+
+			let needs_quotes = *self == Delimiter::Comma
+				&& field
+					.iter()
+					.any(|b| matches!(b, b',' | b'"' | b'\r' | b'\n'));
+
+			if needs_quotes {
+				line.push(b'"');
+
+				for &b in field {
+					// quotes are escaped by doubling them
+					if b == b'"' {
+						line.push(b'"');
+					}
+
+					line.push(b);
+				}
+
+				line.push(b'"');
+			} else {
+				line.extend_from_slice(field);
+			}
+		}
+
+		line
+	}
+}
+
+/// Iterator over the fields of a line, created by `Delimiter::split`
+pub struct Fields<'a> {
+	/// Part of the line not split yet, `None` after the last field
+	rest: Option<&'a [u8]>,
+	delimiter: Delimiter,
+}
+
+impl<'a> Fields<'a> {
+	/// split off the next field at `separator`
+	fn split_at(&mut self, rest: &'a [u8], separator: u8) -> &'a [u8] {
+		match rest.iter().position(|b| *b == separator) {
+			Some(i) => {
+				self.rest = Some(&rest[i + 1..]);
+				&rest[..i]
+			}
+			None => {
+				self.rest = None;
+				rest
+			}
+		}
+	}
+
+	/// split off the next field of a comma separated line, removing quotes
+	fn split_quoted(&mut self, rest: &'a [u8]) -> Cow<'a, [u8]> {
+		if !rest.starts_with(b"\"") {
+			return Cow::Borrowed(self.split_at(rest, b','));
+		}
+
+		let mut field = Vec::new();
+		let mut i = 1;
+
+		while i < rest.len() {
+			match (rest[i], rest.get(i + 1)) {
+				(b'"', Some(b'"')) => {
+					field.push(b'"');
+					i += 2;
+				}
+				(b'"', _) => {
+					i += 1;
+					break;
+				}
+				(b, _) => {
+					field.push(b);
+					i += 1;
+				}
+			}
+		}
+
+		// anything between the closing quote and the next comma is kept
+		let trailing = self.split_at(&rest[i..], b',');
+		field.extend_from_slice(trailing);
+
+		Cow::Owned(field)
+	}
+}
+
+impl<'a> Iterator for Fields<'a> {
+	type Item = Cow<'a, [u8]>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let rest = self.rest?;
+		let is_space = |b: &u8| *b == b' ' || *b == b'\t';
+
+		match self.delimiter {
+			Delimiter::Tab => Some(Cow::Borrowed(self.split_at(rest, b'\t'))),
+			Delimiter::Char(c) => Some(Cow::Borrowed(self.split_at(rest, c))),
+			Delimiter::Comma => Some(self.split_quoted(rest)),
+			Delimiter::Whitespace => {
+				let start = rest.iter().position(|b| !is_space(b))?;
+				let rest = &rest[start..];
+				let end = rest.iter().position(is_space).unwrap_or(rest.len());
+
+				self.rest = Some(&rest[end..]);
+				Some(Cow::Borrowed(&rest[..end]))
+			}
+		}
+	}
+}
@@ -0,0 +1,167 @@
+use clap::ValueEnum;
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+mod bgzf;
+pub use crate::bgzf::BgzfWriter;
+
+mod delimiter;
+pub use crate::delimiter::{Delimiter, Fields};
+
+mod lines;
+pub use crate::lines::{output_ending, LineBuffer, CRLF, LF};
+
+#[cfg(test)]
+mod tests;
+
+/// Path standing for stdin when reading, and stdout when writing
+pub const STDIO: &str = "-";
+
+/// Magic bytes at the start of gzip and bgzip files
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Magic bytes at the start of zstd files
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression of an output file
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Compression {
+	None,
+	Gzip,
+	/// Blocked gzip, which can be indexed by tabix
+	Bgzip,
+	Zstd,
+}
+
+impl Compression {
+	/// select the compression for `path` by its extension.
+	/// `.gz` files are written as bgzip, which any gzip reader can read
+	pub fn from_extension(path: &str) -> Compression {
+		match Path::new(path).extension().and_then(|e| e.to_str()) {
+			Some("gz" | "bgz") => Compression::Bgzip,
+			Some("zst" | "zstd") => Compression::Zstd,
+			_ => Compression::None,
+		}
+	}
+}
+
+pub type Reader = Box<dyn BufRead>;
+pub type Writer = BufWriter<OutputStream>;
+
+/// An output, compressed while writing
+pub enum OutputStream {
+	Plain(Box<dyn Write>),
+	Gzip(GzEncoder<Box<dyn Write>>),
+	Bgzip(BgzfWriter<Box<dyn Write>>),
+	Zstd(zstd::Encoder<'static, Box<dyn Write>>),
+}
+
+impl Write for OutputStream {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		match self {
+			OutputStream::Plain(w) => w.write(buf),
+			OutputStream::Gzip(w) => w.write(buf),
+			OutputStream::Bgzip(w) => w.write(buf),
+			OutputStream::Zstd(w) => w.write(buf),
+		}
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match self {
+			OutputStream::Plain(w) => w.flush(),
+			OutputStream::Gzip(w) => w.flush(),
+			OutputStream::Bgzip(w) => w.flush(),
+			OutputStream::Zstd(w) => w.flush(),
+		}
+	}
+}
+
+impl OutputStream {
+	/// write the end of the compressed stream
+	pub fn finish(self) -> io::Result<()> {
+		match self {
+			OutputStream::Plain(mut w) => w.flush(),
+			OutputStream::Gzip(w) => w.finish().map(|_| ()),
+			OutputStream::Bgzip(w) => w.finish().map(|_| ()),
+			OutputStream::Zstd(w) => w.finish().map(|_| ()),
+		}
+	}
+}
+
+/// check whether `path` stands for stdin or stdout
+pub fn is_stdio(path: &str) -> bool {
+	path == STDIO
+}
+
+/// open `path` for reading, or stdin if `path` is `-`.
+/// Gzip, bgzip and zstd compressed input is detected by its magic bytes and decompressed
+pub fn open(path: &str) -> io::Result<Reader> {
+	if is_stdio(path) {
+		read_from(stdin().lock())
+	} else {
+		read_from(File::open(path)?)
+	}
+}
+
+/// create a reader for `input`, decompressing compressed input
+pub fn read_from(input: impl Read + 'static) -> io::Result<Reader> {
+	let mut input = BufReader::new(input);
+	let magic = input.fill_buf()?;
+
+	if magic.starts_with(&GZIP_MAGIC) {
+		// bgzip files consist of many gzip members
+		Ok(Box::new(BufReader::new(MultiGzDecoder::new(input))))
+	} else if magic.starts_with(&ZSTD_MAGIC) {
+		Ok(Box::new(BufReader::new(zstd::Decoder::with_buffer(input)?)))
+	} else {
+		Ok(Box::new(input))
+	}
+}
+
+/// create `path` for writing, or write to stdout if `path` is `-`.
+/// Without `compression`, it is selected by the extension of `path`
+pub fn create(path: &str, compression: Option<Compression>) -> io::Result<Writer> {
+	let compression = compression.unwrap_or_else(|| Compression::from_extension(path));
+
+	if is_stdio(path) {
+		write_to(stdout().lock(), compression)
+	} else {
+		write_to(File::create(path)?, compression)
+	}
+}
+
+/// create a writer for `output`, compressed with `compression`
+pub fn write_to(output: impl Write + 'static, compression: Compression) -> io::Result<Writer> {
+	let output: Box<dyn Write> = Box::new(output);
+
+	let stream = match compression {
+		Compression::None => OutputStream::Plain(output),
+		Compression::Gzip => {
+			OutputStream::Gzip(GzEncoder::new(output, flate2::Compression::default()))
+		}
+		Compression::Bgzip => OutputStream::Bgzip(BgzfWriter::new(output)),
+		Compression::Zstd => OutputStream::Zstd(zstd::Encoder::new(output, 0)?),
+	};
+
+	Ok(BufWriter::new(stream))
+}
+
+/// flush `writer` and write the end of its compressed stream.
+/// Has to be called once all lines are written
+pub fn finish(writer: Writer) -> io::Result<()> {
+	writer.into_inner().map_err(|e| e.into_error())?.finish()
+}
+
+/// write `report` to `path` as JSON
+pub fn write_json_report(path: &str, report: &impl Serialize) -> io::Result<()> {
+	let mut writer = create(path, Some(Compression::None))?;
+
+	serde_json::to_writer_pretty(&mut writer, report)?;
+	writer.write_all(b"\n")?;
+
+	finish(writer)
+}
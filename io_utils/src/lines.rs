@@ -0,0 +1,134 @@
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+/// Line ending written for new lines and with `--normalize-eol`
+pub const LF: &[u8] = b"\n";
+
+/// Line ending of Windows files
+pub const CRLF: &[u8] = b"\r\n";
+
+/// Raw lines read into a single buffer, which is reused after `clear`.
+/// Lines are not required to be valid UTF-8
+#[derive(Default)]
+pub struct LineBuffer {
+	bytes: Vec<u8>,
+
+	/// Line number, position in `bytes` excluding the line ending,
+	/// and end of the line ending of every line
+	lines: Vec<(usize, Range<usize>, usize)>,
+
+	/// Amount of lines read, including cleared lines
+	lines_read: usize,
+
+	/// Whether the first line ending read is CRLF
+	crlf: Option<bool>,
+
+	/// Whether the last line read is missing its line ending
+	missing_newline: bool,
+}
+
+impl LineBuffer {
+	/// append the next line of `reader` to the buffer.
+	/// Returns `None` at the end of the input
+	pub fn read_line(&mut self, reader: &mut impl BufRead) -> io::Result<Option<&[u8]>> {
+		let start = self.bytes.len();
+
+		if reader.read_until(b'\n', &mut self.bytes)? == 0 {
+			return Ok(None);
+		}
+
+		// line endings are not part of the line, same as for `BufRead::lines`.
+		// `\r` is also removed at the end of the input, so it never ends up in the last column
+		let line_end = self.bytes.len();
+		let mut end = line_end;
+
+		self.missing_newline = !self.bytes[start..end].ends_with(b"\n");
+
+		if !self.missing_newline {
+			end -= 1;
+		}
+
+		if self.bytes[start..end].ends_with(b"\r") {
+			end -= 1;
+		}
+
+		if self.crlf.is_none() && !self.missing_newline {
+			self.crlf = Some(end + 2 == line_end);
+		}
+
+		self.lines.push((self.lines_read, start..end, line_end));
+		self.lines_read += 1;
+
+		Ok(Some(&self.bytes[start..end]))
+	}
+
+	/// get the line number and contents of the `i`th line in the buffer
+	pub fn get(&self, i: usize) -> (usize, &[u8]) {
+		let (line_number, range, _) = &self.lines[i];
+
+		(*line_number, &self.bytes[range.clone()])
+	}
+
+	/// get the line ending of the `i`th line in the buffer as read.
+	/// It is empty for a last line without line ending
+	pub fn ending(&self, i: usize) -> &[u8] {
+		let (_, range, line_end) = &self.lines[i];
+
+		&self.bytes[range.end..*line_end]
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (usize, &[u8])> {
+		(0..self.lines.len()).map(|i| self.get(i))
+	}
+
+	/// iterate over the line number, contents and line ending of every line in the buffer
+	pub fn iter_with_endings(&self) -> impl Iterator<Item = (usize, &[u8], &[u8])> {
+		(0..self.lines.len()).map(|i| {
+			let (line_number, line) = self.get(i);
+			(line_number, line, self.ending(i))
+		})
+	}
+
+	pub fn len(&self) -> usize {
+		self.lines.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.lines.is_empty()
+	}
+
+	/// amount of lines read, which is also the number of the next line
+	pub fn lines_read(&self) -> usize {
+		self.lines_read
+	}
+
+	/// line ending of the input, detected from the first line.
+	/// Used for lines not read from the input
+	pub fn line_ending(&self) -> &'static [u8] {
+		match self.crlf {
+			Some(true) => CRLF,
+			_ => LF,
+		}
+	}
+
+	/// whether the last line read ends with a line ending, or no line was read
+	pub fn ends_with_newline(&self) -> bool {
+		!self.missing_newline
+	}
+
+	/// remove all lines, keeping the allocated memory
+	pub fn clear(&mut self) {
+		self.bytes.clear();
+		self.lines.clear();
+	}
+}
+
+/// line ending to write for a line read with `ending`.
+/// With `normalize`, every line is written with LF
+pub fn output_ending(ending: &[u8], normalize: bool) -> &[u8] {
+	if normalize {
+		LF
+	} else {
+		ending
+	}
+}
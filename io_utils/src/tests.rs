@@ -0,0 +1,196 @@
+use std::fs::{create_dir_all, read, remove_dir_all};
+use std::io::Read;
+
+use super::*;
+
+/// lines of increasing length, adding up to multiple bgzip blocks
+fn test_lines() -> String {
+	(0..5000)
+		.map(|i| format!("chr{}\t{}\t{}\n", i % 22, i * 100, "ACGT".repeat(i % 10)))
+		.collect()
+}
+
+fn write_and_read(path: &str, compression: Option<Compression>) -> String {
+	let mut writer = create(path, compression).unwrap();
+	writer.write_all(test_lines().as_bytes()).unwrap();
+	finish(writer).unwrap();
+
+	let mut contents = String::new();
+	open(path).unwrap().read_to_string(&mut contents).unwrap();
+
+	contents
+}
+
+#[test]
+fn compression_from_extension() {
+	assert_eq!(Compression::from_extension("out.bed"), Compression::None);
+	assert_eq!(
+		Compression::from_extension("out.bed.gz"),
+		Compression::Bgzip
+	);
+	assert_eq!(
+		Compression::from_extension("out.vcf.bgz"),
+		Compression::Bgzip
+	);
+	assert_eq!(
+		Compression::from_extension("out.tabular.zst"),
+		Compression::Zstd
+	);
+}
+
+#[test]
+fn round_trip() {
+	let tmp = ".tmp/1";
+	create_dir_all(tmp).unwrap();
+
+	let files = [
+		("plain.bed", None),
+		("gzip.bed.gz", Some(Compression::Gzip)),
+		("bgzip.bed.gz", None),
+		("zstd.bed.zst", None),
+		("zstd.bed", Some(Compression::Zstd)),
+	];
+
+	for (file, compression) in files {
+		assert_eq!(
+			write_and_read(&format!("{tmp}/{file}"), compression),
+			test_lines()
+		);
+	}
+
+	// only the plain file is written unchanged
+	assert_eq!(
+		read(format!("{tmp}/plain.bed")).unwrap(),
+		test_lines().as_bytes()
+	);
+	assert_eq!(read(format!("{tmp}/gzip.bed.gz")).unwrap()[..2], GZIP_MAGIC);
+	assert_eq!(read(format!("{tmp}/zstd.bed")).unwrap()[..4], ZSTD_MAGIC);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn bgzip_blocks() {
+	let tmp = ".tmp/2";
+	create_dir_all(tmp).unwrap();
+
+	let path = format!("{tmp}/out.bed.gz");
+	write_and_read(&path, None);
+
+	let data = read(&path).unwrap();
+	let mut blocks = Vec::new();
+	let mut offset = 0;
+
+	// every block stores its own size, which is what tabix relies on
+	while offset < data.len() {
+		let block = &data[offset..];
+
+		assert_eq!(block[..4], [0x1f, 0x8b, 0x08, 0x04]);
+		assert_eq!(block[12..14], *b"BC");
+
+		let block_size = u16::from_le_bytes([block[16], block[17]]) as usize + 1;
+		let uncompressed_size =
+			u32::from_le_bytes(block[block_size - 4..block_size].try_into().unwrap());
+
+		blocks.push(uncompressed_size as usize);
+		offset += block_size;
+	}
+
+	assert_eq!(offset, data.len());
+	assert_eq!(blocks.iter().sum::<usize>(), test_lines().len());
+	assert!(blocks.len() > 2);
+	assert_eq!(blocks[0], 0xff00);
+
+	// the file ends with an empty block
+	assert_eq!(blocks.last(), Some(&0));
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn any_input() {
+	let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+	encoder.write_all(test_lines().as_bytes()).unwrap();
+
+	let compressed = std::io::Cursor::new(encoder.finish().unwrap());
+	let mut contents = String::new();
+	read_from(compressed)
+		.unwrap()
+		.read_to_string(&mut contents)
+		.unwrap();
+
+	assert_eq!(contents, test_lines());
+	assert!(is_stdio("-"));
+	assert!(!is_stdio("-.bed"));
+}
+
+#[test]
+fn line_buffer() {
+	let mut input = std::io::Cursor::new(b"a\tb\r\n\xe9\n\nlast".to_vec());
+	let mut buffer = LineBuffer::default();
+
+	assert_eq!(buffer.read_line(&mut input).unwrap(), Some(&b"a\tb"[..]));
+	assert_eq!(buffer.read_line(&mut input).unwrap(), Some(&b"\xe9"[..]));
+	buffer.clear();
+
+	assert!(buffer.is_empty());
+	assert_eq!(buffer.read_line(&mut input).unwrap(), Some(&b""[..]));
+	assert_eq!(buffer.read_line(&mut input).unwrap(), Some(&b"last"[..]));
+	assert_eq!(buffer.read_line(&mut input).unwrap(), None);
+
+	// line numbers continue after clearing
+	assert_eq!(
+		buffer.iter().collect::<Vec<_>>(),
+		vec![(2, &b""[..]), (3, &b"last"[..])]
+	);
+	assert_eq!(buffer.lines_read(), 4);
+}
+
+#[test]
+fn line_endings() {
+	let mut input = std::io::Cursor::new(b"a\r\nb\nc\r".to_vec());
+	let mut buffer = LineBuffer::default();
+
+	while buffer.read_line(&mut input).unwrap().is_some() {}
+
+	// `\r` is removed before the end of the input as well
+	assert_eq!(
+		buffer.iter_with_endings().collect::<Vec<_>>(),
+		vec![
+			(0, &b"a"[..], &b"\r\n"[..]),
+			(1, &b"b"[..], &b"\n"[..]),
+			(2, &b"c"[..], &b"\r"[..])
+		]
+	);
+	assert_eq!(buffer.line_ending(), CRLF);
+	assert!(!buffer.ends_with_newline());
+	assert_eq!(output_ending(buffer.ending(0), true), LF);
+	assert_eq!(output_ending(buffer.ending(0), false), CRLF);
+}
+
+#[test]
+fn delimiters() {
+	let split = |delimiter: &str, line: &[u8]| -> Vec<Vec<u8>> {
+		let delimiter: Delimiter = delimiter.parse().unwrap();
+		delimiter.split(line).map(|f| f.into_owned()).collect()
+	};
+
+	assert_eq!(split("tab", b"a\tb c\t"), vec![&b"a"[..], b"b c", b""]);
+	assert_eq!(split(";", b"a;b,c"), vec![&b"a"[..], b"b,c"]);
+	assert_eq!(
+		split("whitespace", b"  a  b\t c "),
+		vec![&b"a"[..], b"b", b"c"]
+	);
+	assert_eq!(
+		split("comma", br#"chr1,"a, ""quoted"" name",,"x""#),
+		vec![&b"chr1"[..], br#"a, "quoted" name"#, b"", b"x"]
+	);
+	assert!("ab".parse::<Delimiter>().is_err());
+
+	// fields are quoted again when joining
+	assert_eq!(
+		Delimiter::Comma.join(&[&b"chr1"[..], br#"a, "b""#]),
+		br#"chr1,"a, ""b""""#
+	);
+	assert_eq!(Delimiter::Whitespace.join(&["a", "b"]), b"a b");
+}
@@ -1,32 +1,57 @@
 use clap::Parser;
-use std::{
-	fs::File,
-	io::{BufRead, BufReader, BufWriter, Write},
-	process::exit,
-};
+use io_utils::{output_ending, Compression, LineBuffer};
+use serde::Serialize;
+use std::{io::Write, process::exit, time::Instant};
 
 #[cfg(test)]
 mod tests;
 
 #[derive(Parser)]
 pub struct Arguments {
-	/// File to be truncated
+	/// File to be truncated. Use `-` to read from stdin
 	#[arg(short, long)]
 	in_file: String,
 
-	/// File to write to
+	/// File to write to. Use `-` to write to stdout
 	#[arg(short, long)]
 	out_file: String,
 
 	/// Number of lines to remove
 	#[arg(short, long, default_value_t = 1)]
 	num_lines: usize,
+
+	/// Compression of the output file. By default it is selected by its extension.
+	/// Compressed input files are detected automatically
+	#[arg(long, value_enum)]
+	compression: Option<Compression>,
+
+	/// Write all lines with LF line endings.
+	/// By default, lines keep their original line ending, including a missing final newline
+	#[arg(long)]
+	normalize_eol: bool,
+
+	/// File to write the counts of the run to, as JSON
+	#[arg(long)]
+	report_json: Option<String>,
+}
+
+/// Counts of a run, written with `--report-json`
+#[derive(Serialize)]
+struct Report {
+	total_lines: usize,
+	removed_lines: usize,
+	kept_lines: usize,
+	elapsed_seconds: f64,
 }
 
 fn main() {
 	let args = Arguments::parse();
 
 	match run_with_args(&args) {
+		// the report must not end up in the output
+		Ok(_) if io_utils::is_stdio(&args.out_file) => {
+			eprintln!("{} lines removed", args.num_lines)
+		}
 		Ok(_) => println!("{} lines removed", args.num_lines),
 		Err(e) => {
 			eprintln!("{e}");
@@ -36,18 +61,44 @@ fn main() {
 }
 
 pub fn run_with_args(args: &Arguments) -> Result<(), std::io::Error> {
-	let input_file = File::open(&args.in_file)?;
-	let output_file = File::create(&args.out_file)?;
+	let start = Instant::now();
+	let mut reader = io_utils::open(&args.in_file)?;
+	let mut writer = io_utils::create(&args.out_file, args.compression)?;
+
+	let mut buffer = LineBuffer::default();
 
-	let reader = BufReader::new(input_file);
-	let mut writer = BufWriter::new(output_file);
+	// lines are copied as raw bytes, so any encoding is kept unchanged
+	loop {
+		let skip = buffer.lines_read() < args.num_lines;
 
-	for (_, line) in reader.lines().enumerate().skip(args.num_lines) {
-		let line = line?;
-		writer.write(&format!("{line}\n").into_bytes())?;
+		let line = match buffer.read_line(&mut reader)? {
+			Some(l) => l,
+			None => break,
+		};
+
+		if !skip {
+			writer.write_all(line)?;
+			writer.write_all(output_ending(buffer.ending(0), args.normalize_eol))?;
+		}
+
+		buffer.clear();
 	}
 
-	writer.flush()?;
+	io_utils::finish(writer)?;
+
+	if let Some(file) = &args.report_json {
+		let total_lines = buffer.lines_read();
+		let removed_lines = total_lines.min(args.num_lines);
+
+		let report = Report {
+			total_lines,
+			removed_lines,
+			kept_lines: total_lines - removed_lines,
+			elapsed_seconds: start.elapsed().as_secs_f64(),
+		};
+
+		io_utils::write_json_report(file, &report)?;
+	}
 
 	Ok(())
 }
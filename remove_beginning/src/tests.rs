@@ -12,6 +12,9 @@ fn remove_beginning() {
 		in_file: "../test_data/1.bed".to_owned(),
 		out_file: format!("{tmp}/out.bed"),
 		num_lines: 5,
+		compression: None,
+		normalize_eol: false,
+		report_json: None,
 	};
 
 	let res = run_with_args(&args);
@@ -24,3 +27,101 @@ fn remove_beginning() {
 
 	remove_dir_all(tmp).unwrap();
 }
+
+#[test]
+fn compressed_files() {
+	let tmp = ".tmp/2";
+	create_dir_all(tmp).unwrap();
+
+	let compressed = Arguments {
+		in_file: "../test_data/1.bed".to_owned(),
+		out_file: format!("{tmp}/in.bed.gz"),
+		num_lines: 0,
+		compression: None,
+		normalize_eol: false,
+		report_json: None,
+	};
+
+	assert!(run_with_args(&compressed).is_ok());
+
+	let args = Arguments {
+		in_file: compressed.out_file,
+		out_file: format!("{tmp}/out.bed"),
+		num_lines: 5,
+		compression: None,
+		normalize_eol: false,
+		report_json: None,
+	};
+
+	let res = run_with_args(&args);
+
+	assert!(res.is_ok());
+	assert!(is_file_equal(
+		&args.out_file,
+		"../test_data/remove_beginning_test1.bed"
+	));
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn non_utf8_input() {
+	let tmp = ".tmp/3";
+	create_dir_all(tmp).unwrap();
+
+	let args = Arguments {
+		in_file: format!("{tmp}/in.bed"),
+		out_file: format!("{tmp}/out.bed"),
+		num_lines: 1,
+		compression: None,
+		normalize_eol: false,
+		report_json: None,
+	};
+
+	std::fs::write(
+		&args.in_file,
+		b"# caf\xe9\nchr1\tM\xfcller\nchr2\t\xff\xfe\n",
+	)
+	.unwrap();
+
+	assert!(run_with_args(&args).is_ok());
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"chr1\tM\xfcller\nchr2\t\xff\xfe\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}
+
+#[test]
+fn line_endings() {
+	let tmp = ".tmp/4";
+	create_dir_all(tmp).unwrap();
+
+	let mut args = Arguments {
+		in_file: format!("{tmp}/in.bed"),
+		out_file: format!("{tmp}/out.bed"),
+		num_lines: 1,
+		compression: None,
+		normalize_eol: false,
+		report_json: None,
+	};
+
+	std::fs::write(&args.in_file, b"header\r\nchr1\t1\r\nchr2\t2").unwrap();
+
+	assert!(run_with_args(&args).is_ok());
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"chr1\t1\r\nchr2\t2"
+	);
+
+	args.normalize_eol = true;
+
+	assert!(run_with_args(&args).is_ok());
+	assert_eq!(
+		std::fs::read(&args.out_file).unwrap(),
+		b"chr1\t1\nchr2\t2\n"
+	);
+
+	remove_dir_all(tmp).unwrap();
+}